@@ -0,0 +1,162 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rayon::prelude::ParallelIterator;
+
+use wolfenlike::input::InputManager;
+use wolfenlike::map::Map;
+use wolfenlike::renderer::Renderer;
+use wolfenlike::scene::DrawCommand;
+use wolfenlike::textures;
+use wolfenlike::{App, Entity, EntityType, HEIGHT, WIDTH};
+
+/// builds the default map with the game's textures, drawing into memory
+fn setup() -> App {
+    let renderer = Renderer::headless(WIDTH, HEIGHT);
+    let mut app = App::new(renderer, InputManager::new(), 0);
+    app.textures
+        .load_manifest("./images/textures.ron")
+        .expect("failed to load textures");
+    app.textures
+        .push_missing_texture(textures::missing_texture());
+    app
+}
+
+/// a 100x100 level walled in around the edge with a pillar every few tiles
+fn large_map() -> (Map, Map, Map) {
+    const SIZE: usize = 100;
+    let walls = (0..SIZE)
+        .map(|y| {
+            (0..SIZE)
+                .map(|x| {
+                    let edge = x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1;
+                    let pillar = x % 6 == 3 && y % 6 == 3;
+                    u32::from(edge || pillar)
+                })
+                .collect()
+        })
+        .collect();
+    let floor = vec![vec![1; SIZE]; SIZE];
+    let ceiling = vec![vec![2; SIZE]; SIZE];
+    (Map::new(walls), Map::new(floor), Map::new(ceiling))
+}
+
+/// places the player at the given tile position facing along the given direction
+fn place(app: &mut App, x: f32, y: f32, dir_x: f32, dir_y: f32) {
+    app.player_x = x;
+    app.player_y = y;
+    app.dir_x = dir_x;
+    app.dir_y = dir_y;
+    app.set_fov(66.0);
+}
+
+/// the views draws are timed from, along with their names
+fn scenes() -> Vec<(&'static str, App)> {
+    // the whole view is a wall right in front of the player
+    let mut facing_wall = setup();
+    place(&mut facing_wall, 1.5, 1.5, 0.0, -1.0);
+
+    // looking down the long side of the room, mostly floor and ceiling
+    let mut open_room = setup();
+    open_room.entities.clear();
+    place(&mut open_room, 1.5, 8.5, 1.0, 0.0);
+
+    // the same room filled with barrels
+    let mut many_sprites = setup();
+    many_sprites.entities.clear();
+    for y in 6..9 {
+        for x in 4..8 {
+            for (dx, dy) in [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+                many_sprites.entities.push(Entity::new(
+                    x as f32 + dx,
+                    y as f32 + dy,
+                    4,
+                    EntityType::Stationary,
+                ));
+            }
+        }
+    }
+    place(&mut many_sprites, 8.5, 7.5, -1.0, 0.0);
+
+    // looking across a level far bigger than the screen, with the minimap scrolled to the middle
+    let mut large = setup();
+    let (walls, floor, ceiling) = large_map();
+    large.set_map("large", walls, floor, ceiling);
+    large.show_minimap = true;
+    place(&mut large, 50.5, 50.5, 1.0, 0.0);
+
+    vec![
+        ("facing_wall", facing_wall),
+        ("open_room", open_room),
+        ("many_sprites", many_sprites),
+        ("large_map", large),
+    ]
+}
+
+fn draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw");
+    for (name, mut app) in scenes() {
+        group.bench_function(name, |b| b.iter(|| app.draw()));
+    }
+    group.finish();
+}
+
+/// which part of the draw a command belongs to, if it's one that's timed on its own
+fn part_of(command: &DrawCommand) -> Option<&'static str> {
+    match command {
+        DrawCommand::TextureColumn { .. } => Some("walls"),
+        DrawCommand::Floor => Some("floor"),
+        DrawCommand::Sprite { .. } => Some("sprites"),
+        _ => None,
+    }
+}
+
+/// each part of a draw on its own. casting covers the rays for every wall
+/// column, the rest only draw the commands of one kind from the cast frame
+fn draw_parts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw_parts");
+    for (name, mut app) in scenes() {
+        group.bench_function(format!("{name}/cast"), |b| b.iter(|| app.render_scene()));
+
+        for part in ["walls", "floor", "sprites"] {
+            let mut frame = app.render_scene();
+            frame
+                .commands
+                .retain(|command| part_of(command) == Some(part));
+            group.bench_function(format!("{name}/{part}"), |b| {
+                b.iter(|| frame.apply(&mut app.renderer, &app.textures))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// filling the frame a pixel at a time against whole rows at once, the
+/// floor and ceiling are cast a row at a time for this reason
+fn frame_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_writes");
+    let mut renderer = Renderer::headless(WIDTH, HEIGHT);
+    let color = [0x40, 0x80, 0xc0, 0xff];
+
+    group.bench_function("draw_pixel", |b| {
+        b.iter(|| {
+            for y in 0..HEIGHT {
+                for x in 0..WIDTH {
+                    renderer.draw_pixel(&color, x, y);
+                }
+            }
+        })
+    });
+    group.bench_function("rows_mut", |b| {
+        b.iter(|| {
+            renderer.rows_mut().for_each(|row| {
+                for pixel in row.chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&color);
+                }
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, draw, draw_parts, frame_writes);
+criterion_main!(benches);
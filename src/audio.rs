@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use anyhow::{Context, Result};
+use rodio::source::ChannelVolume;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+/// distance in tiles past which positional sounds are silent
+const MAX_AUDIBLE_DIST: f32 = 16.0;
+/// quietest a positional sound gets while still in range
+const MIN_VOLUME: f32 = 0.1;
+
+/// plays sound effects loaded into memory at startup
+pub struct AudioManager {
+    handle: OutputStreamHandle,
+    /// the output stream lives on its own thread, which stops once this is dropped
+    _stream_guard: Sender<()>,
+    sounds: Vec<Vec<u8>>,
+    names: HashMap<String, usize>,
+}
+
+impl AudioManager {
+    /// opens the default output device and loads every wav and ogg file in the directory.
+    /// sounds are given ids in file name order and named after the file without its extension
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        // the stream can't be shared between threads so it is kept on its own
+        // thread, which lets the rest of the game be used from the renderer's workers
+        let (handle_tx, handle_rx) = mpsc::channel();
+        let (guard_tx, guard_rx) = mpsc::channel::<()>();
+        thread::spawn(move || match OutputStream::try_default() {
+            Ok((_stream, handle)) => {
+                let _ = handle_tx.send(Ok(handle));
+                // blocks until the manager is dropped
+                let _ = guard_rx.recv();
+            }
+            Err(err) => {
+                let _ = handle_tx.send(Err(err));
+            }
+        });
+        let handle = handle_rx
+            .recv()
+            .context("audio thread stopped unexpectedly")?
+            .context("failed to open audio output device")?;
+
+        let dir = dir.as_ref();
+        let mut paths = fs::read_dir(dir)
+            .with_context(|| format!("failed to read sound directory {}", dir.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| {
+            path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("wav") || ext.eq_ignore_ascii_case("ogg")
+            })
+        });
+        paths.sort();
+
+        let mut sounds = Vec::new();
+        let mut names = HashMap::new();
+        for path in paths {
+            let data = fs::read(&path)
+                .with_context(|| format!("failed to load sound {}", path.display()))?;
+            if let Some(stem) = path.file_stem() {
+                names.insert(stem.to_string_lossy().into_owned(), sounds.len());
+            }
+            sounds.push(data);
+        }
+
+        Ok(Self {
+            handle,
+            _stream_guard: guard_tx,
+            sounds,
+            names,
+        })
+    }
+
+    /// looks up the id of a sound by name
+    pub fn sound_id(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// plays the sound once in the background
+    pub fn play(&self, sound_id: usize) -> Result<()> {
+        let source = self.decode(sound_id)?;
+        let sink = Sink::try_new(&self.handle)?;
+        sink.append(source);
+        // detaching lets the sound finish playing after the sink is dropped
+        sink.detach();
+        Ok(())
+    }
+
+    /// plays the sound once as if it came from the given world position, getting
+    /// quieter with distance and panned toward the side of the player it is on
+    pub fn play_at(
+        &self,
+        sound_id: usize,
+        world_x: f32,
+        world_y: f32,
+        player_x: f32,
+        player_y: f32,
+        player_dir: (f32, f32),
+    ) -> Result<()> {
+        let to_x = world_x - player_x;
+        let to_y = world_y - player_y;
+        let dist = (to_x * to_x + to_y * to_y).sqrt();
+        if dist > MAX_AUDIBLE_DIST {
+            return Ok(());
+        }
+        let volume = (1.0 - dist / MAX_AUDIBLE_DIST).max(MIN_VOLUME);
+
+        // -1.0 is fully left and 1.0 is fully right, sounds on top of the player are centered
+        let pan = if dist > f32::EPSILON {
+            let (dir_x, dir_y) = player_dir;
+            let dir_len = (dir_x * dir_x + dir_y * dir_y).sqrt().max(f32::EPSILON);
+            (to_x * -dir_y + to_y * dir_x) / (dist * dir_len)
+        } else {
+            0.0
+        };
+        let left = volume * (1.0 - pan).min(1.0);
+        let right = volume * (1.0 + pan).min(1.0);
+
+        let source = ChannelVolume::new(self.decode(sound_id)?, vec![left, right]);
+        let sink = Sink::try_new(&self.handle)?;
+        sink.append(source);
+        // detaching lets the sound finish playing after the sink is dropped
+        sink.detach();
+        Ok(())
+    }
+
+    fn decode(&self, sound_id: usize) -> Result<Decoder<Cursor<Vec<u8>>>> {
+        let Some(data) = self.sounds.get(sound_id) else {
+            anyhow::bail!("no sound with id {sound_id}");
+        };
+        Ok(Decoder::new(Cursor::new(data.clone()))?)
+    }
+}
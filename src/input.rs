@@ -1,13 +1,98 @@
-#![allow(dead_code)]
-
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::{Duration, Instant};
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{PhysicalPosition, PhysicalSize},
     event::{
-        DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent,
+        DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta,
+        VirtualKeyCode, WindowEvent,
     },
 };
 
+/// number of frames averaged by `InputManager::fps` by default
+const DEFAULT_FPS_SAMPLES: usize = 60;
+
+/// pixels of scrolling treated as one notch of a scroll wheel
+const SCROLL_PIXELS_PER_LINE: f64 = 100.0;
+
+/// analog stick values smaller than this are treated as centered
+#[cfg(feature = "gamepad")]
+const GAMEPAD_DEADZONE: f32 = 0.15;
+
+/// gamepad buttons that can be queried from the `InputManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    RightTrigger,
+    Start,
+    Select,
+}
+
+/// logical actions that can be bound to an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Sprint,
+    Shoot,
+    Use,
+    ToggleFullscreen,
+    Pause,
+    Quit,
+}
+
+/// a physical input that an action can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+/// maps each action to the input that triggers it
+pub struct InputBindings {
+    bindings: HashMap<Action, Binding>,
+}
+
+impl InputBindings {
+    /// binds the action to the given input, replacing any previous binding
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        self.bindings.insert(action, binding);
+    }
+
+    /// returns the input the action is bound to, if any
+    pub fn get(&self, action: Action) -> Option<Binding> {
+        self.bindings.get(&action).copied()
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (Action::Forward, Binding::Key(VirtualKeyCode::W)),
+                (Action::Back, Binding::Key(VirtualKeyCode::S)),
+                (Action::StrafeLeft, Binding::Key(VirtualKeyCode::A)),
+                (Action::StrafeRight, Binding::Key(VirtualKeyCode::D)),
+                (Action::TurnLeft, Binding::Key(VirtualKeyCode::Left)),
+                (Action::TurnRight, Binding::Key(VirtualKeyCode::Right)),
+                (Action::Sprint, Binding::Key(VirtualKeyCode::LShift)),
+                (Action::Use, Binding::Key(VirtualKeyCode::E)),
+                (Action::Shoot, Binding::Mouse(MouseButton::Left)),
+                (Action::ToggleFullscreen, Binding::Key(VirtualKeyCode::F)),
+                (Action::Pause, Binding::Key(VirtualKeyCode::Escape)),
+                (Action::Quit, Binding::Key(VirtualKeyCode::Q)),
+            ]),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct InputManager {
     just_pressed: Vec<VirtualKeyCode>,
@@ -15,16 +100,53 @@ pub struct InputManager {
     released: Vec<VirtualKeyCode>,
     pub request_exit: bool,
     pub request_resize: Option<PhysicalSize<u32>>,
-    mouse_motion: (f64, f64),
-    mouse_buttons: [bool; 2],
-    old_mouse_buttons: [bool; 2],
+    /// the window was last resized to nothing, which is how minimizing is reported
+    pub minimized: bool,
+    /// the window lost focus and the cursor should be freed
+    pub request_release_cursor: bool,
+    /// the window regained focus and the cursor can be captured again
+    pub request_grab_cursor: bool,
+    pub bindings: InputBindings,
+    /// reads mouse motion straight from the device, skipping the os pointer acceleration
+    /// applied to cursor movement. on by default, turn off to aim with the accelerated cursor
+    pub use_raw_input: bool,
+    /// unaccelerated motion from device events this frame
+    raw_mouse_motion: (f64, f64),
+    /// accelerated motion from cursor movement this frame
+    cursor_motion: (f64, f64),
+    /// where the cursor was last seen in the window
+    last_cursor_position: Option<PhysicalPosition<f64>>,
+    scroll_delta: f32,
+    /// mouse buttons currently held, any button the mouse reports is tracked
+    mouse_buttons: HashSet<MouseButton>,
+    /// mouse buttons held at the start of the previous frame
+    old_mouse_buttons: HashSet<MouseButton>,
     start_time: Option<Instant>,
     delta_time: Option<Duration>,
+    frame_times: VecDeque<Duration>,
+    fps_samples: usize,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    gamepad_move: (f32, f32),
+    gamepad_look: (f32, f32),
+    gamepad_held: Vec<GamepadButton>,
+    gamepad_just_pressed: Vec<GamepadButton>,
 }
 
 impl InputManager {
     pub fn new() -> Self {
+        Self::with_fps_samples(DEFAULT_FPS_SAMPLES)
+    }
+
+    /// creates an input manager that averages the fps over the given number of frames
+    pub fn with_fps_samples(fps_samples: usize) -> Self {
         Self {
+            frame_times: VecDeque::with_capacity(fps_samples),
+            fps_samples: fps_samples.max(1),
+            use_raw_input: true,
+            // no gamepad support is available if gilrs fails to initialize
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
             ..Default::default()
         }
     }
@@ -36,11 +158,16 @@ impl InputManager {
             Event::NewEvents(_) => {
                 self.request_resize = None;
                 self.request_exit = false;
+                self.request_release_cursor = false;
+                self.request_grab_cursor = false;
                 self.just_pressed.clear();
                 self.released.clear();
 
-                self.mouse_motion = (0.0, 0.0);
-                self.old_mouse_buttons = self.mouse_buttons;
+                self.raw_mouse_motion = (0.0, 0.0);
+                self.cursor_motion = (0.0, 0.0);
+                self.scroll_delta = 0.0;
+                self.old_mouse_buttons.clone_from(&self.mouse_buttons);
+                self.gamepad_just_pressed.clear();
 
                 self.start_time.get_or_insert(Instant::now());
                 self.delta_time = None;
@@ -51,6 +178,26 @@ impl InputManager {
                 ..
             } => {
                 self.request_resize = Some(*size);
+                self.minimized = size.width == 0 || size.height == 0;
+                false
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Focused(focused),
+                ..
+            } => {
+                if *focused {
+                    self.request_grab_cursor = true;
+                } else {
+                    self.request_release_cursor = true;
+                    // release events for inputs held while unfocused never arrive,
+                    // so forget all of them to avoid stuck movement
+                    self.held.clear();
+                    self.just_pressed.clear();
+                    self.released.clear();
+                    self.mouse_buttons.clear();
+                    // also forgotten so the cleared buttons don't read as just released
+                    self.old_mouse_buttons.clear();
+                }
                 false
             }
             Event::WindowEvent {
@@ -73,7 +220,7 @@ impl InputManager {
                     },
                 ..
             } => {
-                if !self.just_pressed.contains(&keycode) {
+                if !self.just_pressed.contains(keycode) {
                     self.just_pressed.push(*keycode);
                     self.held.push(*keycode);
                 }
@@ -83,19 +230,42 @@ impl InputManager {
                 event: WindowEvent::MouseInput { button, state, .. },
                 ..
             } => {
-                let state = *state == ElementState::Pressed;
-                match button {
-                    MouseButton::Left => self.mouse_buttons[0] = state,
-                    MouseButton::Right => self.mouse_buttons[1] = state,
-                    _ => (),
-                }
+                match state {
+                    ElementState::Pressed => self.mouse_buttons.insert(*button),
+                    ElementState::Released => self.mouse_buttons.remove(button),
+                };
+                false
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.y / SCROLL_PIXELS_PER_LINE) as f32
+                    }
+                };
                 false
             }
             Event::DeviceEvent {
                 event: DeviceEvent::MouseMotion { delta },
                 ..
             } => {
-                self.mouse_motion = *delta;
+                // several motion events can arrive in a single frame
+                self.raw_mouse_motion.0 += delta.0;
+                self.raw_mouse_motion.1 += delta.1;
+                false
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                if let Some(last) = self.last_cursor_position {
+                    self.cursor_motion.0 += position.x - last.x;
+                    self.cursor_motion.1 += position.y - last.y;
+                }
+                self.last_cursor_position = Some(*position);
                 false
             }
             Event::WindowEvent {
@@ -117,7 +287,7 @@ impl InputManager {
                         .held
                         .iter()
                         .filter(|&key| key != keycode)
-                        .map(|key| *key)
+                        .copied()
                         .collect::<Vec<VirtualKeyCode>>();
                 }
                 false
@@ -126,12 +296,72 @@ impl InputManager {
                 self.delta_time = self.start_time.map(|time| time.elapsed());
                 self.start_time = Some(Instant::now());
 
+                if let Some(delta) = self.delta_time {
+                    if self.frame_times.len() == self.fps_samples {
+                        self.frame_times.pop_front();
+                    }
+                    self.frame_times.push_back(delta);
+                }
+
+                #[cfg(feature = "gamepad")]
+                self.poll_gamepad();
+
                 true
             }
             _ => false,
         }
     }
 
+    /// drains pending gamepad events, updating the stick and button state
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepad(&mut self) {
+        use gilrs::{Axis, EventType};
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    let Some(button) = GamepadButton::from_gilrs(button) else {
+                        continue;
+                    };
+                    if !self.gamepad_held.contains(&button) {
+                        self.gamepad_just_pressed.push(button);
+                        self.gamepad_held.push(button);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = GamepadButton::from_gilrs(button) {
+                        self.gamepad_held.retain(|held| *held != button);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    let value = if value.abs() < GAMEPAD_DEADZONE {
+                        0.0
+                    } else {
+                        value
+                    };
+                    match axis {
+                        Axis::LeftStickX => self.gamepad_move.0 = value,
+                        Axis::LeftStickY => self.gamepad_move.1 = value,
+                        Axis::RightStickX => self.gamepad_look.0 = value,
+                        Axis::RightStickY => self.gamepad_look.1 = value,
+                        _ => (),
+                    }
+                }
+                EventType::Disconnected => {
+                    self.gamepad_move = (0.0, 0.0);
+                    self.gamepad_look = (0.0, 0.0);
+                    self.gamepad_held.clear();
+                    self.gamepad_just_pressed.clear();
+                }
+                _ => (),
+            }
+        }
+    }
+
     /// returns whether or not the given key was just pressed
     pub fn is_just_pressed(&self, key: VirtualKeyCode) -> bool {
         self.just_pressed.contains(&key)
@@ -152,26 +382,209 @@ impl InputManager {
         self.delta_time
     }
 
-    /// returns the mouse motion since the last frame
+    /// frames per second averaged over the most recent frames
+    pub fn fps(&self) -> f32 {
+        let total = self.frame_times.iter().sum::<Duration>().as_secs_f32();
+        if total > 0.0 {
+            self.frame_times.len() as f32 / total
+        } else {
+            0.0
+        }
+    }
+
+    /// time the current frame started
+    pub fn frame_start(&self) -> Option<Instant> {
+        self.start_time
+    }
+
+    /// returns the mouse motion since the last frame, from the device when
+    /// `use_raw_input` is set and from the cursor otherwise
     pub fn mouse_motion(&self) -> (f64, f64) {
-        self.mouse_motion
+        if self.use_raw_input {
+            self.raw_mouse_motion
+        } else {
+            self.cursor_motion
+        }
+    }
+
+    /// returns the unaccelerated mouse motion since the last frame, whichever mode is in use
+    pub fn raw_mouse_motion(&self) -> (f64, f64) {
+        self.raw_mouse_motion
+    }
+
+    /// returns how many notches the scroll wheel moved this frame, positive is up
+    pub fn scroll_delta(&self) -> f32 {
+        self.scroll_delta
     }
 
     /// returns if the given mouse button is currently down
     pub fn is_mouse_down(&self, button: MouseButton) -> bool {
-        match button {
-            MouseButton::Left => self.mouse_buttons[0],
-            MouseButton::Right => self.mouse_buttons[1],
-            _ => false,
-        }
+        self.mouse_buttons.contains(&button)
     }
 
     /// returns if the given mouse button was pressed this frame
     pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button) && !self.old_mouse_buttons.contains(&button)
+    }
+
+    /// returns if the given mouse button was released this frame
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        !self.mouse_buttons.contains(&button) && self.old_mouse_buttons.contains(&button)
+    }
+
+    /// returns whether or not the input bound to the action is currently down
+    pub fn is_action_down(&self, action: Action) -> bool {
+        match self.bindings.get(action) {
+            Some(Binding::Key(key)) => self.is_down(key),
+            Some(Binding::Mouse(button)) => self.is_mouse_down(button),
+            None => false,
+        }
+    }
+
+    /// returns whether or not the input bound to the action was just pressed
+    pub fn is_action_just_pressed(&self, action: Action) -> bool {
+        match self.bindings.get(action) {
+            Some(Binding::Key(key)) => self.is_just_pressed(key),
+            Some(Binding::Mouse(button)) => self.is_mouse_just_pressed(button),
+            None => false,
+        }
+    }
+
+    /// returns whether or not the input bound to the action was just released
+    pub fn is_action_just_released(&self, action: Action) -> bool {
+        match self.bindings.get(action) {
+            Some(Binding::Key(key)) => self.is_just_released(key),
+            Some(Binding::Mouse(button)) => self.is_mouse_just_released(button),
+            None => false,
+        }
+    }
+
+    /// returns the left stick position, x is right and y is forward
+    pub fn gamepad_move_axis(&self) -> (f32, f32) {
+        self.gamepad_move
+    }
+
+    /// returns the right stick position, x is right and y is up
+    pub fn gamepad_look_axis(&self) -> (f32, f32) {
+        self.gamepad_look
+    }
+
+    /// returns if the given gamepad button is currently down
+    pub fn is_gamepad_down(&self, button: GamepadButton) -> bool {
+        self.gamepad_held.contains(&button)
+    }
+
+    /// returns if the given gamepad button was pressed this frame
+    pub fn is_gamepad_just_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepad_just_pressed.contains(&button)
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadButton {
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
         match button {
-            MouseButton::Left => self.mouse_buttons[0] && !self.old_mouse_buttons[0],
-            MouseButton::Right => self.mouse_buttons[1] && !self.old_mouse_buttons[1],
-            _ => false,
+            gilrs::Button::South => Some(Self::South),
+            gilrs::Button::East => Some(Self::East),
+            gilrs::Button::North => Some(Self::North),
+            gilrs::Button::West => Some(Self::West),
+            gilrs::Button::LeftTrigger2 => Some(Self::LeftTrigger),
+            gilrs::Button::RightTrigger2 => Some(Self::RightTrigger),
+            gilrs::Button::Start => Some(Self::Start),
+            gilrs::Button::Select => Some(Self::Select),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use winit::event::{DeviceId, StartCause};
+    use winit::window::WindowId;
+
+    fn window_event(event: WindowEvent<'static>) -> Event<'static, ()> {
+        Event::WindowEvent {
+            // only ever compared against other ids, which nothing here does
+            window_id: unsafe { WindowId::dummy() },
+            event,
         }
     }
+
+    /// starts a new frame, like the event loop waking up
+    pub(crate) fn new_frame() -> Event<'static, ()> {
+        Event::NewEvents(StartCause::Poll)
+    }
+
+    #[allow(deprecated)]
+    pub(crate) fn key(keycode: VirtualKeyCode, state: ElementState) -> Event<'static, ()> {
+        window_event(WindowEvent::KeyboardInput {
+            device_id: unsafe { DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(keycode),
+                modifiers: Default::default(),
+            },
+            is_synthetic: false,
+        })
+    }
+
+    #[allow(deprecated)]
+    pub(crate) fn mouse(button: MouseButton, state: ElementState) -> Event<'static, ()> {
+        window_event(WindowEvent::MouseInput {
+            device_id: unsafe { DeviceId::dummy() },
+            state,
+            button,
+            modifiers: Default::default(),
+        })
+    }
+
+    pub(crate) fn focus(focused: bool) -> Event<'static, ()> {
+        window_event(WindowEvent::Focused(focused))
+    }
+
+    #[test]
+    fn focus_loss_releases_held_keys() {
+        let mut input = InputManager::new();
+        input.process_event(&new_frame());
+        input.process_event(&key(VirtualKeyCode::W, ElementState::Pressed));
+        assert!(input.is_down(VirtualKeyCode::W));
+
+        input.process_event(&focus(false));
+        assert!(!input.is_down(VirtualKeyCode::W));
+        assert!(!input.is_just_pressed(VirtualKeyCode::W));
+    }
+
+    #[test]
+    fn focus_loss_does_not_report_mouse_release() {
+        let mut input = InputManager::new();
+        input.process_event(&new_frame());
+        input.process_event(&mouse(MouseButton::Left, ElementState::Pressed));
+        input.process_event(&new_frame());
+        assert!(input.is_mouse_down(MouseButton::Left));
+
+        input.process_event(&focus(false));
+        assert!(!input.is_mouse_down(MouseButton::Left));
+        assert!(!input.is_mouse_just_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn mouse_just_released_lasts_one_frame() {
+        let mut input = InputManager::new();
+        input.process_event(&new_frame());
+        input.process_event(&mouse(MouseButton::Left, ElementState::Pressed));
+        input.process_event(&mouse(MouseButton::Right, ElementState::Pressed));
+        input.process_event(&new_frame());
+
+        input.process_event(&new_frame());
+        input.process_event(&mouse(MouseButton::Left, ElementState::Released));
+        assert!(input.is_mouse_just_released(MouseButton::Left));
+        // the other button is still held so it isn't released along with it
+        assert!(!input.is_mouse_just_released(MouseButton::Right));
+        assert!(input.is_mouse_down(MouseButton::Right));
+
+        input.process_event(&new_frame());
+        assert!(!input.is_mouse_just_released(MouseButton::Left));
+    }
 }
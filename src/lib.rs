@@ -0,0 +1,2842 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use image::math::Rect;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator};
+use rayon::prelude::ParallelIterator;
+use serde::{Deserialize, Serialize};
+use winit::dpi::PhysicalSize;
+use winit::event::VirtualKeyCode;
+
+use input::{Action, GamepadButton, InputManager};
+use map::Map;
+use renderer::{fog_amount, Camera, Renderer};
+use scaler::ScalingMode;
+use scene::{DrawCommand, FrameCommands};
+use textures::TextureManager;
+
+const SCALE: i32 = 4;
+pub const WIDTH: i32 = 240 * SCALE;
+pub const HEIGHT: i32 = 135 * SCALE;
+
+const MIN_FOV: f32 = 30.0;
+const MAX_FOV: f32 = 120.0;
+const FOV_STEP: f32 = 5.0;
+
+/// size in pixels of a single tile on the minimap
+const MINIMAP_CELL_SIZE: i32 = 2 * SCALE;
+/// tiles shown on the minimap in each direction from the player
+const MINIMAP_RADIUS: i32 = 8;
+
+/// length in pixels of each crosshair line
+const CROSSHAIR_SIZE: i32 = 2 * SCALE;
+/// distance in pixels between the screen center and each crosshair line
+const CROSSHAIR_GAP: i32 = SCALE;
+
+/// file used by the quick-save and quick-load keys
+const QUICKSAVE_PATH: &str = "./quicksave.ron";
+
+/// name shown in the window title
+pub const TITLE: &str = "Wolfenstein Clone";
+
+/// seconds between fps readouts in the window title
+const FPS_TITLE_INTERVAL: f32 = 1.0;
+/// seconds between window title updates in debug mode, setting it every frame is slow
+const TITLE_UPDATE_INTERVAL: f32 = 0.5;
+
+/// furthest the horizon can be moved up or down, as a fraction of the screen height
+const MAX_PITCH: f32 = 0.5;
+
+/// half the width of the player's collision box, in tiles
+const PLAYER_RADIUS: f32 = 0.2;
+/// closest distance walls and sprites are drawn at, anything nearer is drawn as if it
+/// were this far away instead of stretching a few texels over the whole screen.
+/// it's inside the player's collision box so it only matters when stuck in a wall
+const NEAR_CLIP: f32 = 0.1;
+/// gap left between the player and a wall they were pushed out of
+const COLLISION_EPSILON: f32 = 0.001;
+
+/// squared distance at which a projectile hits an entity
+const PROJECTILE_HIT_DIST_SQ: f32 = 0.16;
+/// how far ahead of the shooter a projectile starts, just outside their collision box
+const PROJECTILE_SPAWN_OFFSET: f32 = PLAYER_RADIUS + 0.1;
+/// textures projectiles flicker between
+const PROJECTILE_FRAMES: [usize; 2] = [5, 6];
+/// seconds each projectile frame is shown for
+const PROJECTILE_FRAME_TIME: f32 = 0.05;
+
+/// size of the barrels in the default map relative to a wall
+const BARREL_SCALE: f32 = 0.75;
+
+/// how quickly the head bob eases in and out, in full bobs per second
+const BOB_EASE_SPEED: f32 = 4.0;
+
+/// health the player starts with
+const PLAYER_MAX_HEALTH: i32 = 100;
+/// health lost when the player is hit by a projectile
+const PROJECTILE_DAMAGE: i32 = 10;
+
+/// ammo the player starts with
+const STARTING_AMMO: u32 = 50;
+/// squared distance within which the player collects a pickup
+const PICKUP_DIST_SQ: f32 = 0.25;
+/// health given by a health pickup
+const HEALTH_PICKUP_AMOUNT: i32 = 25;
+/// ammo given by an ammo pickup
+const AMMO_PICKUP_AMOUNT: u32 = 10;
+
+/// half the width of an enemy's collision box, in tiles
+const ENEMY_RADIUS: f32 = 0.3;
+/// distance from the player at which enemies stop walking toward them
+const ENEMY_STOP_DIST: f32 = 0.75;
+/// seconds between shots from an enemy that can see the player, also how long
+/// it takes them to react after first spotting the player
+const ENEMY_FIRE_INTERVAL: f32 = 1.5;
+/// tiles per second an enemy's projectiles travel, slow enough to dodge
+const ENEMY_PROJECTILE_SPEED: f32 = 4.0;
+/// seconds between path recalculations for an enemy that can't see the player
+const PATH_RECOMPUTE_INTERVAL: f32 = 0.5;
+/// how close to the center of a path tile an enemy has to get before moving on to the next
+const PATH_WAYPOINT_DIST: f32 = 0.1;
+
+/// tiles per second a push wall slides at once activated
+const PUSH_WALL_SPEED: f32 = 1.0;
+
+/// weapons the scroll wheel cycles through, in order
+const WEAPONS: [WeaponKind; 2] = [WeaponKind::Projectile, WeaponKind::Hitscan];
+/// furthest a hitscan shot can hit something, in tiles
+const HITSCAN_RANGE: f32 = 32.0;
+/// how close to an entity's center a hitscan shot has to pass to hit it
+const HITSCAN_HIT_RADIUS: f32 = 0.3;
+
+/// radians per second turned with the right stick fully pushed
+const GAMEPAD_TURN_SPEED: f32 = 3.0;
+/// screen heights per second the horizon moves with the right stick fully pushed
+const GAMEPAD_PITCH_SPEED: f32 = 1.0;
+
+/// directory sound effects are loaded from
+#[cfg(feature = "audio")]
+const SOUNDS_DIR: &str = "./sounds";
+
+/// distance in tiles lit up by a muzzle flash
+const MUZZLE_FLASH_RADIUS: f32 = 5.0;
+
+/// wall brightness a level starts with
+const DEFAULT_AMBIENT: f32 = 1.0;
+/// brightness of x and y side walls a level starts with
+const DEFAULT_SIDE_SHADE: [f32; 2] = [0.6, 1.0];
+
+/// height of the weapon viewmodel as a fraction of the screen height
+const VIEWMODEL_SIZE: f32 = 0.5;
+/// how far the viewmodel sways side to side while walking, as a fraction of the screen width
+const VIEWMODEL_SWAY: f32 = 0.02;
+/// how far the viewmodel kicks up when firing, as a fraction of the screen height
+const VIEWMODEL_RECOIL: f32 = 0.05;
+
+/// most particles alive at once, new ones are dropped past this
+const MAX_PARTICLES: usize = 256;
+/// particles spawned where a projectile hits a wall
+const IMPACT_PARTICLES: usize = 8;
+/// seconds an impact particle lives for
+const PARTICLE_LIFETIME: f32 = 0.4;
+/// tiles per second an impact particle flies outward at
+const PARTICLE_SPEED: f32 = 1.5;
+/// width of a particle, in tiles
+const PARTICLE_SIZE: f32 = 0.03;
+
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod input;
+pub mod map;
+pub mod renderer;
+pub mod scaler;
+pub mod scene;
+pub mod textures;
+
+pub struct App {
+    pub renderer: Renderer,
+    pub input_manager: InputManager,
+    map_name: String,
+    pub player_x: f32,
+    pub player_y: f32,
+    pub dir_x: f32,
+    pub dir_y: f32,
+    pub plane_x: f32,
+    pub plane_y: f32,
+    fov_degrees: f32,
+    /// the player dies when this reaches zero
+    pub health: i32,
+    /// shots left, pickups add to it
+    pub ammo: u32,
+    /// ammo the player is given when a level starts
+    pub starting_ammo: u32,
+    /// shooting doesn't use up ammo when set
+    pub infinite_ammo: bool,
+    /// seconds until the weapon can fire again
+    fire_cooldown: f32,
+    /// shots per second the weapon can fire at
+    pub fire_rate: f32,
+    /// keeps firing while the shoot button is held instead of once per press
+    pub automatic: bool,
+    current_weapon: WeaponKind,
+    /// scrolling that hasn't yet added up to a full weapon switch
+    weapon_scroll: f32,
+    /// tiles per second the player moves at
+    pub walk_speed: f32,
+    /// tiles per second the player moves at while sprinting
+    pub run_speed: f32,
+    /// radians turned per unit of mouse motion
+    pub mouse_sensitivity: f32,
+    /// radians per second turned while a turn key is held
+    pub keyboard_turn_speed: f32,
+    /// how quickly the smoothed mouse motion follows the raw motion, 1.0 is unsmoothed
+    pub mouse_smoothing: f32,
+    smoothed_motion_x: f32,
+    /// shared with the last drawn frame, so changing a tile copies the map if
+    /// that frame still holds it
+    walls: Arc<Map>,
+    /// textures for each face of a wall tile, tiles not in here show the
+    /// texture with the id one below the tile's on every face
+    pub wall_textures: HashMap<u32, WallTextures>,
+    /// height of a wall tile as a fraction of a full wall
+    pub wall_heights: HashMap<u32, f32>,
+    /// geometry of wall tiles, tiles not in here are solid blocks
+    pub wall_shapes: HashMap<u32, WallShape>,
+    /// wall tiles that cycle through texture ids, each shown for the given number of seconds
+    pub wall_animations: HashMap<u32, (Vec<usize>, f32)>,
+    /// seconds of unpaused play, drives the wall animations
+    animation_time: f32,
+    /// wall tiles that slide away when the player uses them
+    push_wall_tiles: HashSet<u32>,
+    /// push walls that are currently sliding
+    push_walls: Vec<PushWall>,
+    /// tiles push walls have finished sliding into, they can't be pushed again
+    spent_push_walls: HashSet<(i32, i32)>,
+    floor: Arc<Map>,
+    ceiling: Arc<Map>,
+    pub entities: Vec<Entity>,
+    particles: Vec<Particle>,
+    pub textures: TextureManager,
+    /// brightness multiplier applied to every wall
+    pub ambient: f32,
+    /// brightness of walls hit on the x and y sides, shading them differently adds depth
+    pub side_shade: [f32; 2],
+    /// lights placed around the level
+    pub lights: Vec<Light>,
+    /// light that follows the player around, its own position is ignored
+    pub player_light: Option<Light>,
+    /// seconds left of the current muzzle flash
+    muzzle_flash_timer: f32,
+    /// seconds a muzzle flash lasts for
+    pub muzzle_flash_duration: f32,
+    /// brightness the muzzle flash adds at the player when it starts
+    pub muzzle_flash_intensity: f32,
+    /// tint fading out over the screen edges, if one is showing
+    screen_flash: Option<ScreenFlash>,
+    /// flash shown when the player takes damage
+    pub hurt_flash: ScreenFlash,
+    /// flash shown when the player collects a pickup
+    pub pickup_flash: ScreenFlash,
+    /// weapon drawn at the bottom of the screen
+    pub viewmodel_texture: Option<usize>,
+    /// seconds left of the current recoil kick
+    recoil_timer: f32,
+    /// seconds the viewmodel takes to kick up and settle back after firing
+    recoil_duration: f32,
+    #[cfg(feature = "audio")]
+    audio: Option<audio::AudioManager>,
+    /// color distant walls, floors and sprites fade into
+    pub fog_color: [u8; 4],
+    /// how quickly things fade into the fog with distance, 0.0 disables fog
+    pub fog_density: f32,
+    /// vertical look offset as a fraction of the screen height, positive looks up
+    pitch: f32,
+    bob_phase: f32,
+    /// how far the view bobs up and down, as a fraction of the screen height
+    pub bob_amplitude: f32,
+    /// how fast the view bobs while walking, in radians per tile moved
+    pub bob_frequency: f32,
+    /// eases the bob in while moving and back to center when standing still
+    bob_blend: f32,
+    pub show_minimap: bool,
+    pub paused: bool,
+    /// source of all randomness in the game, thread rng would make runs unrepeatable
+    rng: StdRng,
+    /// casts the wall rays across threads, otherwise one column at a time on this
+    /// thread. both give the same frame since each column is cast on its own
+    pub parallel_raycast: bool,
+    /// squared distance past which entities aren't drawn
+    pub max_sprite_dist_sq: f32,
+    /// what's drawn above the horizon
+    pub sky_mode: SkyMode,
+    /// last frame drawn, its buffers are refilled by the next draw
+    frame: FrameCommands,
+    /// caps the frame rate when set, otherwise frames are drawn as fast as possible
+    pub target_fps: Option<u32>,
+    /// shows the average fps in the window title every `FPS_TITLE_INTERVAL` seconds
+    show_fps: bool,
+    fps_timer: f32,
+    /// shows the fps and player position in the window title, toggled with F1
+    debug: bool,
+    /// moves the player straight through walls, toggled with N in debug mode
+    noclip: bool,
+    /// freezes the game except for single steps, toggled with F6 in debug mode
+    single_step: bool,
+    /// advances the frozen game by one frame on the next update, set with shift + period
+    step_requested: bool,
+    title_timer: f32,
+    /// window title to set on the next frame
+    pub pending_title: Option<String>,
+}
+
+/// texture ids used for each face of a wall tile
+#[derive(Clone, Copy)]
+pub struct WallTextures {
+    pub north: usize,
+    pub south: usize,
+    pub east: usize,
+    pub west: usize,
+}
+
+/// first wall hit by a ray cast with `cast_ray`
+#[derive(Default, Clone, Copy)]
+pub struct Ray {
+    pub ray_dir_x: f32,
+    pub ray_dir_y: f32,
+    /// distance to the hit in multiples of the ray's direction
+    pub ray_dist: f32,
+    /// tile the ray stopped in
+    pub map_x: i32,
+    pub map_y: i32,
+    /// 0 if the ray hit a face running along the y axis, 1 along the x axis
+    pub side: i32,
+    /// where along the wall the ray hit, from 0.0 to 1.0
+    pub wall_x: f32,
+}
+
+/// geometry of a wall tile, anything other than `Solid` is a thin
+/// segment inside the tile that rays can pass beside
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WallShape {
+    Solid,
+    /// runs along the x axis through the middle of the tile
+    ThinHorizontal,
+    /// runs along the y axis through the middle of the tile
+    ThinVertical,
+    /// runs from the tile's top left corner to its bottom right corner
+    DiagonalDown,
+    /// runs from the tile's bottom left corner to its top right corner
+    DiagonalUp,
+}
+
+/// what fills the screen above the horizon
+#[derive(Clone, Copy, PartialEq)]
+pub enum SkyMode {
+    /// the ceiling tiles are cast like the floor
+    Ceiling,
+    /// panoramic texture that turns with the player
+    Texture(usize),
+    /// plain colors fading from the top of the screen down to the horizon
+    Gradient { zenith: [u8; 4], horizon: [u8; 4] },
+}
+
+/// how a weapon reaches what it's fired at
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WeaponKind {
+    /// fires a bullet that travels across the map
+    Projectile,
+    /// instantly hits the first thing in the center of the screen
+    Hitscan,
+}
+
+/// who fired a projectile, projectiles never hit their owner
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Owner {
+    Player,
+    Enemy,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum EntityType {
+    Stationary,
+    Projectile(f32, f32, Owner),
+    Enemy { speed: f32, health: i32 },
+    Pickup { kind: PickupKind },
+}
+
+/// what a pickup gives the player when collected
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    Health,
+    Ammo,
+}
+
+/// cycles through a list of textures at a fixed rate
+#[derive(Clone, Serialize, Deserialize)]
+struct Animation {
+    frames: Vec<usize>,
+    frame_time: f32,
+    elapsed: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entity {
+    x_pos: f32,
+    y_pos: f32,
+    texture_id: usize,
+    entity_type: EntityType,
+    animation: Option<Animation>,
+    /// size of the sprite relative to a full wall
+    #[serde(default = "default_scale")]
+    scale: f32,
+    /// how far the sprite is pushed down from being centered on the horizon,
+    /// as a fraction of its own height
+    #[serde(default)]
+    v_offset: f32,
+    /// angle on the map the entity is facing, in radians
+    #[serde(default)]
+    facing: f32,
+    /// textures showing the entity from eight directions, used instead of the
+    /// animation when set. the first is seen from in front and the rest go
+    /// counterclockwise around the entity on the map in 45 degree steps
+    #[serde(default)]
+    rotations: Option<[usize; 8]>,
+    /// tiles left to walk through to reach the player when they can't be seen
+    #[serde(skip)]
+    path: Vec<(i32, i32)>,
+    /// seconds until the path is recomputed
+    #[serde(skip)]
+    repath_timer: f32,
+    /// seconds until an enemy fires at the player
+    #[serde(skip, default = "default_attack_timer")]
+    attack_timer: f32,
+}
+
+/// wall tile sliding one tile away from the player who pushed it, it blocks both the
+/// tile it left and the one it's moving into until it finishes
+#[derive(Clone, Copy)]
+pub struct PushWall {
+    /// tile the wall started in
+    x: i32,
+    y: i32,
+    /// direction the wall slides in, one tile along a single axis
+    dir_x: i32,
+    dir_y: i32,
+    /// how far the wall has slid, from 0.0 to 1.0
+    progress: f32,
+}
+
+/// brightens walls, floors and ceilings within its radius
+#[derive(Clone, Copy)]
+pub struct Light {
+    pub x_pos: f32,
+    pub y_pos: f32,
+    /// distance in tiles at which the light no longer has any effect
+    pub radius: f32,
+    /// brightness added to each color channel at the center of the light, 1.0 is full white
+    pub color: [f32; 3],
+}
+
+/// colored tint over the edges of the screen that fades out, shown when
+/// something happens to the player
+#[derive(Clone, Copy)]
+pub struct ScreenFlash {
+    /// color at the edges when the flash starts, its alpha sets the strength
+    color: [u8; 4],
+    /// seconds the flash takes to fade out
+    duration: f32,
+    /// seconds left before the flash is gone
+    timer: f32,
+}
+
+/// short lived dot drawn for effects like projectile impacts
+struct Particle {
+    x_pos: f32,
+    y_pos: f32,
+    x_vel: f32,
+    y_vel: f32,
+    /// seconds left before the particle is removed
+    lifetime: f32,
+    color: [u8; 4],
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_attack_timer() -> f32 {
+    ENEMY_FIRE_INTERVAL
+}
+
+/// everything written to a save file
+#[derive(Serialize, Deserialize)]
+struct SaveState {
+    map_name: String,
+    player_x: f32,
+    player_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+    entities: Vec<Entity>,
+}
+
+/// blends the color toward the fog color based on its distance
+fn apply_fog(color: [u8; 4], dist: f32, density: f32, fog_color: [u8; 4]) -> [u8; 4] {
+    let amount = fog_amount(dist, density);
+    [
+        (color[0] as f32 * (1.0 - amount) + fog_color[0] as f32 * amount) as u8,
+        (color[1] as f32 * (1.0 - amount) + fog_color[1] as f32 * amount) as u8,
+        (color[2] as f32 * (1.0 - amount) + fog_color[2] as f32 * amount) as u8,
+        color[3],
+    ]
+}
+
+/// moves a square with the given half width centered at x,y, stopping it flush
+/// against any walls. each axis is resolved separately so it slides along walls
+fn try_move(walls: &Map, x: f32, y: f32, move_x: f32, move_y: f32, radius: f32) -> (f32, f32) {
+    let mut x = x;
+    let mut y = y;
+
+    let new_x = x + move_x;
+    if !overlaps_wall(walls, new_x, y, radius) {
+        x = new_x;
+    } else if move_x > 0.0 {
+        x = (new_x + radius).floor() - radius - COLLISION_EPSILON;
+    } else if move_x < 0.0 {
+        x = (new_x - radius).floor() + 1.0 + radius + COLLISION_EPSILON;
+    }
+
+    let new_y = y + move_y;
+    if !overlaps_wall(walls, x, new_y, radius) {
+        y = new_y;
+    } else if move_y > 0.0 {
+        y = (new_y + radius).floor() - radius - COLLISION_EPSILON;
+    } else if move_y < 0.0 {
+        y = (new_y - radius).floor() + 1.0 + radius + COLLISION_EPSILON;
+    }
+
+    (x, y)
+}
+
+/// returns whether a straight line between the two points crosses no wall tiles.
+/// steps from tile to tile the same way rays are cast
+fn line_of_sight(walls: &Map, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+    let dir_x = x1 - x0;
+    let dir_y = y1 - y0;
+    let mut map_x = x0.floor() as i32;
+    let mut map_y = y0.floor() as i32;
+    let end_x = x1.floor() as i32;
+    let end_y = y1.floor() as i32;
+
+    // distances are measured as a fraction of the whole line, so anything past 1.0 is beyond the end
+    let delta_dist_x = (1.0 / dir_x).abs();
+    let delta_dist_y = (1.0 / dir_y).abs();
+    let (step_x, mut side_dist_x) = if dir_x < 0.0 {
+        (-1, (x0 - map_x as f32) * delta_dist_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - x0) * delta_dist_x)
+    };
+    let (step_y, mut side_dist_y) = if dir_y < 0.0 {
+        (-1, (y0 - map_y as f32) * delta_dist_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - y0) * delta_dist_y)
+    };
+
+    while (map_x, map_y) != (end_x, end_y) && side_dist_x.min(side_dist_y) <= 1.0 {
+        if side_dist_x < side_dist_y {
+            side_dist_x += delta_dist_x;
+            map_x += step_x;
+        } else {
+            side_dist_y += delta_dist_y;
+            map_y += step_y;
+        }
+
+        if walls.is_solid(map_x, map_y) {
+            return false;
+        }
+    }
+    true
+}
+
+/// finds the shortest path between two tiles moving only up, down, left and right
+/// through empty tiles. the path includes both the start and the goal
+fn pathfind(walls: &Map, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+    let is_open = |(x, y): (i32, i32)| !walls.is_solid(x, y);
+    if !is_open(goal) {
+        return None;
+    }
+    // manhattan distance never overestimates on a 4-connected grid
+    let heuristic = |(x, y): (i32, i32)| (x - goal.0).abs() + (y - goal.1).abs();
+
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::new();
+    let mut cost = HashMap::from([(start, 0)]);
+    open.push(Reverse((heuristic(start), start)));
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![current];
+            let mut tile = current;
+            while let Some(&previous) = came_from.get(&tile) {
+                path.push(previous);
+                tile = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let next_cost = cost[&current] + 1;
+        let (x, y) = current;
+        for neighbor in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if !is_open(neighbor) || cost.get(&neighbor).is_some_and(|&c| c <= next_cost) {
+                continue;
+            }
+            cost.insert(neighbor, next_cost);
+            came_from.insert(neighbor, current);
+            open.push(Reverse((next_cost + heuristic(neighbor), neighbor)));
+        }
+    }
+    None
+}
+
+/// total brightness added by the lights at the given world position
+fn light_at(lights: &[Light], x: f32, y: f32) -> [f32; 3] {
+    let mut total = [0.0; 3];
+    for light in lights {
+        let dist_sq = (light.x_pos - x).powi(2) + (light.y_pos - y).powi(2);
+        // cheap rejection before taking the square root
+        if dist_sq >= light.radius * light.radius {
+            continue;
+        }
+        let falloff = 1.0 - dist_sq.sqrt() / light.radius;
+        for (channel, color) in total.iter_mut().zip(light.color) {
+            *channel += color * falloff * falloff;
+        }
+    }
+    total
+}
+
+/// what each color channel of a texel is multiplied by on a surface with the
+/// given shading and light falling on it. walls, floors and ceilings all go
+/// through this so lit surfaces brighten the same way, up to the texel's own color
+fn lit_brightness(shade: f32, light: [f32; 3]) -> [f32; 3] {
+    light.map(|channel| (shade + channel).clamp(0.0, 1.0))
+}
+
+/// returns whether a square with the given half width centered at x,y
+/// touches any wall tile or leaves the map
+fn overlaps_wall(walls: &Map, x: f32, y: f32, radius: f32) -> bool {
+    let min_x = (x - radius).floor() as i32;
+    let max_x = (x + radius).floor() as i32;
+    let min_y = (y - radius).floor() as i32;
+    let max_y = (y + radius).floor() as i32;
+
+    (min_y..=max_y).any(|cell_y| (min_x..=max_x).any(|cell_x| walls.is_solid(cell_x, cell_y)))
+}
+
+/// which of eight directions something facing the given angle is seen from, 0 is
+/// straight in front and the rest go counterclockwise. `to_viewer` points from the
+/// thing being looked at toward whoever is looking at it
+fn view_octant(facing: f32, to_viewer_x: f32, to_viewer_y: f32) -> usize {
+    let octant = std::f32::consts::FRAC_PI_4;
+    // wrapped into a single turn so facings past a full turn or negative still line up
+    let relative = (to_viewer_y.atan2(to_viewer_x) - facing).rem_euclid(std::f32::consts::TAU);
+    // offset by half an octant so each one is centered on its direction
+    ((relative + octant / 2.0) / octant) as usize % 8
+}
+
+/// the preferred position if its tile is open, otherwise the center of the closest
+/// open tile so the player doesn't start stuck inside a wall. falls back to the
+/// preferred position if the map has no open tiles at all
+pub fn find_valid_spawn(walls: &Map, preferred: (f32, f32)) -> (f32, f32) {
+    if !walls.is_solid(preferred.0.floor() as i32, preferred.1.floor() as i32) {
+        return preferred;
+    }
+    // spawns outside the map start searching from the closest edge tile
+    let start = (
+        (preferred.0.floor() as i32)
+            .min(walls.width() as i32 - 1)
+            .max(0),
+        (preferred.1.floor() as i32)
+            .min(walls.height() as i32 - 1)
+            .max(0),
+    );
+
+    // breadth first through every tile, walls included, so the search spreads
+    // outward evenly instead of following corridors
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        if !walls.is_solid(x, y) {
+            return (x as f32 + 0.5, y as f32 + 0.5);
+        }
+        for neighbor in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if walls.contains(neighbor.0, neighbor.1) && visited.insert(neighbor) {
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    preferred
+}
+
+impl Entity {
+    pub fn new(x_pos: f32, y_pos: f32, texture_id: usize, entity_type: EntityType) -> Self {
+        Self {
+            x_pos,
+            y_pos,
+            texture_id,
+            entity_type,
+            animation: None,
+            scale: 1.0,
+            v_offset: 0.0,
+            facing: 0.0,
+            rotations: None,
+            path: Vec::new(),
+            repath_timer: 0.0,
+            attack_timer: ENEMY_FIRE_INTERVAL,
+        }
+    }
+
+    /// animates the entity through the given textures, showing each for `frame_time` seconds
+    pub fn with_animation(mut self, frames: Vec<usize>, frame_time: f32) -> Self {
+        self.animation = Some(Animation {
+            frames,
+            frame_time,
+            elapsed: 0.0,
+        });
+        self
+    }
+
+    /// resizes the sprite, keeping it resting on the floor
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self.v_offset = (1.0 / scale - 1.0) / 2.0;
+        self
+    }
+
+    /// shows a different texture depending on which way the entity is seen from
+    pub fn with_rotations(mut self, rotations: [usize; 8]) -> Self {
+        self.rotations = Some(rotations);
+        self
+    }
+
+    /// turns the entity to face the given angle on the map, in radians
+    pub fn with_facing(mut self, facing: f32) -> Self {
+        self.facing = facing;
+        self
+    }
+
+    /// the texture to draw the entity with this frame when seen from the given position
+    fn current_texture(&self, viewer_x: f32, viewer_y: f32) -> usize {
+        if let Some(rotations) = &self.rotations {
+            return rotations
+                [view_octant(self.facing, viewer_x - self.x_pos, viewer_y - self.y_pos)];
+        }
+        match &self.animation {
+            Some(animation) if !animation.frames.is_empty() && animation.frame_time > 0.0 => {
+                let frame = (animation.elapsed / animation.frame_time) as usize;
+                animation.frames[frame % animation.frames.len()]
+            }
+            _ => self.texture_id,
+        }
+    }
+}
+
+impl WallTextures {
+    /// picks the face that was hit based on the ray's side and direction
+    fn face(&self, side: i32, ray_dir_x: f32, ray_dir_y: f32) -> usize {
+        if side == 0 {
+            if ray_dir_x > 0.0 {
+                self.west
+            } else {
+                self.east
+            }
+        } else if ray_dir_y > 0.0 {
+            self.north
+        } else {
+            self.south
+        }
+    }
+}
+
+impl WallShape {
+    /// end points of the shape's wall segment inside the given tile,
+    /// `None` for solid tiles
+    fn segment(&self, map_x: i32, map_y: i32) -> Option<((f32, f32), (f32, f32))> {
+        let (x, y) = (map_x as f32, map_y as f32);
+        match self {
+            WallShape::Solid => None,
+            WallShape::ThinHorizontal => Some(((x, y + 0.5), (x + 1.0, y + 0.5))),
+            WallShape::ThinVertical => Some(((x + 0.5, y), (x + 0.5, y + 1.0))),
+            WallShape::DiagonalDown => Some(((x, y), (x + 1.0, y + 1.0))),
+            WallShape::DiagonalUp => Some(((x, y + 1.0), (x + 1.0, y))),
+        }
+    }
+
+    /// which side the wall is shaded as
+    fn side(&self) -> i32 {
+        match self {
+            WallShape::ThinHorizontal => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl PushWall {
+    /// returns whether the wall is moving out of or into the given tile
+    fn covers(&self, map_x: i32, map_y: i32) -> bool {
+        (map_x, map_y) == (self.x, self.y)
+            || (map_x, map_y) == (self.x + self.dir_x, self.y + self.dir_y)
+    }
+
+    /// top left corner of the wall at its current position
+    fn position(&self) -> (f32, f32) {
+        (
+            self.x as f32 + self.dir_x as f32 * self.progress,
+            self.y as f32 + self.dir_y as f32 * self.progress,
+        )
+    }
+}
+
+/// casts a ray from the origin through the walls with DDA, stopping at the first wall
+/// it hits or the edge of the map. the direction doesn't need to be normalized, the
+/// returned distance is in multiples of it
+pub fn cast_ray(
+    walls: &Map,
+    wall_shapes: &HashMap<u32, WallShape>,
+    push_walls: &[PushWall],
+    origin_x: f32,
+    origin_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+) -> Ray {
+    let mut map_x = origin_x as i32;
+    let mut map_y = origin_y as i32;
+
+    let delta_dist_x = (1.0 / dir_x).abs();
+    let delta_dist_y = (1.0 / dir_y).abs();
+
+    let mut hit = 0;
+    let mut side = 0;
+    // distance and wall position of a hit against a thin or sliding wall
+    let mut thin_hit = None;
+
+    let (step_x, mut side_dist_x) = if dir_x < 0.0 {
+        (-1, (origin_x - map_x as f32) * delta_dist_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - origin_x) * delta_dist_x)
+    };
+    let (step_y, mut side_dist_y) = if dir_y < 0.0 {
+        (-1, (origin_y - map_y as f32) * delta_dist_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - origin_y) * delta_dist_y)
+    };
+
+    // DDA algorithm
+    while hit == 0 {
+        if side_dist_x < side_dist_y {
+            side_dist_x += delta_dist_x;
+            map_x += step_x;
+            side = 0;
+        } else {
+            side_dist_y += delta_dist_y;
+            map_y += step_y;
+            side = 1;
+        }
+
+        if !walls.contains(map_x, map_y) {
+            hit = 1;
+            continue;
+        }
+
+        let tile = walls.tile_at(map_x, map_y);
+        if tile == 0 {
+            continue;
+        }
+
+        // sliding walls are hit wherever they've moved to within their two tiles
+        let push_wall = push_walls.iter().find(|wall| wall.covers(map_x, map_y));
+        if let Some(push_wall) = push_wall {
+            if let Some((dist, along, face)) =
+                intersect_tile((origin_x, origin_y), (dir_x, dir_y), push_wall.position())
+            {
+                side = face;
+                thin_hit = Some((dist, along));
+                hit = 1;
+            }
+            continue;
+        }
+
+        let shape = wall_shapes.get(&tile).copied();
+        match shape.and_then(|shape| shape.segment(map_x, map_y)) {
+            // thin walls are only hit if the ray crosses the segment,
+            // otherwise it keeps going through the tile
+            Some((start, end)) => {
+                if let Some(intersection) =
+                    intersect_segment((origin_x, origin_y), (dir_x, dir_y), start, end)
+                {
+                    side = shape.map_or(side, |shape| shape.side());
+                    thin_hit = Some(intersection);
+                    hit = 1;
+                }
+            }
+            None => hit = 1,
+        }
+    }
+
+    let (perp_wall_dist, wall_x) = match thin_hit {
+        Some(intersection) => intersection,
+        None => {
+            // correct fish-eye effect
+            let perp_wall_dist = if side == 0 {
+                side_dist_x - delta_dist_x
+            } else {
+                side_dist_y - delta_dist_y
+            };
+
+            // used to index into wall texture
+            let wall_x = if side == 0 {
+                origin_y + perp_wall_dist * dir_y
+            } else {
+                origin_x + perp_wall_dist * dir_x
+            };
+            (perp_wall_dist, wall_x - wall_x.floor())
+        }
+    };
+
+    Ray {
+        ray_dir_x: dir_x,
+        ray_dir_y: dir_y,
+        ray_dist: perp_wall_dist,
+        map_x,
+        map_y,
+        side,
+        wall_x,
+    }
+}
+
+impl ScreenFlash {
+    /// flash starting at the given color and fading out over `duration` seconds
+    pub fn new(color: [u8; 4], duration: f32) -> Self {
+        Self {
+            color,
+            duration,
+            timer: duration,
+        }
+    }
+
+    /// the color with its alpha faded by how much of the flash is left
+    fn current_color(&self) -> [u8; 4] {
+        let fade = if self.duration > 0.0 {
+            (self.timer / self.duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let [r, g, b, a] = self.color;
+        [r, g, b, (a as f32 * fade) as u8]
+    }
+}
+
+/// intersects a ray with a line segment, returning the distance along the ray
+/// in multiples of its direction and how far along the segment the hit is
+fn intersect_segment(
+    origin: (f32, f32),
+    dir: (f32, f32),
+    start: (f32, f32),
+    end: (f32, f32),
+) -> Option<(f32, f32)> {
+    let seg_x = end.0 - start.0;
+    let seg_y = end.1 - start.1;
+    let denom = dir.0 * seg_y - dir.1 * seg_x;
+    // parallel rays never hit the segment
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let to_start_x = start.0 - origin.0;
+    let to_start_y = start.1 - origin.1;
+    let dist = (to_start_x * seg_y - to_start_y * seg_x) / denom;
+    let along = (to_start_x * dir.1 - to_start_y * dir.0) / denom;
+
+    (dist > 0.0 && (0.0..=1.0).contains(&along)).then_some((dist, along))
+}
+
+/// intersects a ray with a one tile square whose top left corner is at `corner`, returning
+/// the distance along the ray in multiples of its direction, how far along the hit face
+/// the hit is, and the side of the face that was hit
+fn intersect_tile(
+    origin: (f32, f32),
+    dir: (f32, f32),
+    corner: (f32, f32),
+) -> Option<(f32, f32, i32)> {
+    // range of distances along the ray spent between the two edges on one axis
+    let slab = |origin: f32, dir: f32, min: f32| {
+        if dir == 0.0 {
+            return (min..=min + 1.0)
+                .contains(&origin)
+                .then_some((f32::NEG_INFINITY, f32::INFINITY));
+        }
+        let t0 = (min - origin) / dir;
+        let t1 = (min + 1.0 - origin) / dir;
+        Some((t0.min(t1), t0.max(t1)))
+    };
+    let (enter_x, exit_x) = slab(origin.0, dir.0, corner.0)?;
+    let (enter_y, exit_y) = slab(origin.1, dir.1, corner.1)?;
+
+    let enter = enter_x.max(enter_y);
+    if enter <= 0.0 || enter > exit_x.min(exit_y) {
+        return None;
+    }
+    if enter_x > enter_y {
+        Some((enter, origin.1 + enter * dir.1 - corner.1, 0))
+    } else {
+        Some((enter, origin.0 + enter * dir.0 - corner.0, 1))
+    }
+}
+
+impl From<u32> for WallTextures {
+    /// uses the same texture on every face of a plain wall tile
+    fn from(tile: u32) -> Self {
+        let id = tile as usize - 1;
+        Self {
+            north: id,
+            south: id,
+            east: id,
+            west: id,
+        }
+    }
+}
+
+impl App {
+    /// Create a new `World` instance that can draw a moving box. everything random
+    /// is drawn from `seed`, so the same seed and input play out the same way
+    pub fn new(renderer: Renderer, input_manager: InputManager, seed: u64) -> Self {
+        // the view is as wide as the frame so pixels come out square
+        let aspect = renderer.width() as f32 / renderer.height() as f32;
+        let mut app = Self {
+            map_name: String::from("default"),
+            player_x: 1.5,
+            player_y: 1.5,
+            dir_x: -1.0,
+            dir_y: 0.0,
+            plane_x: 0.0,
+            plane_y: aspect / 2.0,
+            fov_degrees: (aspect / 2.0).atan().to_degrees() * 2.0,
+            health: PLAYER_MAX_HEALTH,
+            ammo: STARTING_AMMO,
+            starting_ammo: STARTING_AMMO,
+            infinite_ammo: false,
+            fire_cooldown: 0.0,
+            fire_rate: 4.0,
+            automatic: false,
+            current_weapon: WeaponKind::Projectile,
+            weapon_scroll: 0.0,
+            walk_speed: 5.0,
+            run_speed: 8.0,
+            mouse_sensitivity: 0.003,
+            keyboard_turn_speed: 3.0,
+            mouse_smoothing: 0.5,
+            smoothed_motion_x: 0.0,
+            particles: Vec::new(),
+            textures: TextureManager::new(),
+            ambient: DEFAULT_AMBIENT,
+            side_shade: DEFAULT_SIDE_SHADE,
+            lights: Vec::new(),
+            player_light: None,
+            muzzle_flash_timer: 0.0,
+            muzzle_flash_duration: 0.08,
+            muzzle_flash_intensity: 0.6,
+            screen_flash: None,
+            hurt_flash: ScreenFlash::new([0xff, 0x00, 0x00, 0xaa], 0.4),
+            pickup_flash: ScreenFlash::new([0x00, 0xff, 0x00, 0x66], 0.25),
+            viewmodel_texture: None,
+            recoil_timer: 0.0,
+            recoil_duration: 0.15,
+            // the game is silent if there's no audio device or sounds to load
+            #[cfg(feature = "audio")]
+            audio: audio::AudioManager::new(SOUNDS_DIR)
+                .map_err(|err| eprintln!("audio disabled: {err:#}"))
+                .ok(),
+            fog_color: [0, 0, 0, 0xff],
+            fog_density: 0.0,
+            pitch: 0.0,
+            bob_phase: 0.0,
+            bob_amplitude: 0.01,
+            bob_frequency: 2.5,
+            bob_blend: 0.0,
+            show_minimap: false,
+            paused: false,
+            rng: StdRng::seed_from_u64(seed),
+            parallel_raycast: true,
+            max_sprite_dist_sq: 1024.0,
+            sky_mode: SkyMode::Ceiling,
+            frame: FrameCommands::default(),
+            target_fps: None,
+            show_fps: false,
+            fps_timer: 0.0,
+            debug: false,
+            noclip: false,
+            single_step: false,
+            step_requested: false,
+            title_timer: 0.0,
+            pending_title: None,
+            renderer,
+            input_manager,
+            walls: Arc::new(Map::new(vec![
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![2, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                vec![1, 1, 0, 1, 0, 0, 1, 0, 0, 1],
+                vec![1, 0, 0, 1, 0, 0, 1, 0, 0, 1],
+                vec![1, 0, 0, 1, 3, 1, 1, 0, 1, 1],
+                vec![1, 0, 0, 1, 0, 0, 0, 0, 0, 1],
+                vec![1, 0, 0, 1, 0, 0, 0, 0, 0, 1],
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+            ])),
+            wall_textures: HashMap::new(),
+            wall_heights: HashMap::new(),
+            wall_shapes: HashMap::new(),
+            wall_animations: HashMap::new(),
+            animation_time: 0.0,
+            push_wall_tiles: HashSet::from([3]),
+            push_walls: Vec::new(),
+            spent_push_walls: HashSet::new(),
+            floor: Arc::new(Map::new(vec![
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+            ])),
+            ceiling: Arc::new(Map::new(vec![
+                vec![2, 3, 2, 3, 2, 3, 2, 3, 2, 3],
+                vec![3, 2, 3, 2, 3, 2, 3, 2, 3, 2],
+                vec![2, 3, 2, 3, 2, 3, 2, 3, 2, 3],
+                vec![3, 2, 3, 2, 3, 2, 3, 2, 3, 2],
+                vec![2, 3, 2, 3, 2, 3, 2, 3, 2, 3],
+                vec![3, 2, 3, 2, 3, 2, 3, 2, 3, 2],
+                vec![2, 3, 2, 3, 2, 3, 2, 3, 2, 3],
+                vec![3, 2, 3, 2, 3, 2, 3, 2, 3, 2],
+                vec![2, 3, 2, 3, 2, 3, 2, 3, 2, 3],
+                vec![3, 2, 3, 2, 3, 2, 3, 2, 3, 2],
+            ])),
+            entities: vec![
+                Entity::new(8.5, 1.5, 3, EntityType::Stationary),
+                Entity::new(8.5, 4.5, 3, EntityType::Stationary),
+                Entity::new(8.5, 2.5, 4, EntityType::Stationary).with_scale(BARREL_SCALE),
+                Entity::new(8.5, 3.5, 4, EntityType::Stationary).with_scale(BARREL_SCALE),
+                Entity::new(8.0, 3.0, 4, EntityType::Stationary).with_scale(BARREL_SCALE),
+            ],
+        };
+        (app.player_x, app.player_y) = find_valid_spawn(&app.walls, (app.player_x, app.player_y));
+        app
+    }
+
+    /// swaps in a new level, clearing anything left over from the previous one.
+    /// the lighting goes back to the defaults and the level's lights are removed
+    /// so each level can set its own. the player starts it with `starting_ammo`
+    pub fn set_map(&mut self, name: &str, walls: Map, floor: Map, ceiling: Map) {
+        self.map_name = name.to_owned();
+        self.walls = Arc::new(walls);
+        self.floor = Arc::new(floor);
+        self.ceiling = Arc::new(ceiling);
+        self.entities.clear();
+        self.particles.clear();
+        self.push_walls.clear();
+        self.spent_push_walls.clear();
+        self.ambient = DEFAULT_AMBIENT;
+        self.side_shade = DEFAULT_SIDE_SHADE;
+        self.lights.clear();
+        self.ammo = self.starting_ammo;
+        (self.player_x, self.player_y) =
+            find_valid_spawn(&self.walls, (self.player_x, self.player_y));
+    }
+
+    /// sets the horizontal field of view, keeping the current facing direction
+    pub fn set_fov(&mut self, degrees: f32) {
+        self.fov_degrees = degrees.clamp(MIN_FOV, MAX_FOV);
+        let plane_len = (self.fov_degrees.to_radians() / 2.0).tan();
+        self.plane_x = self.dir_y * plane_len;
+        self.plane_y = -self.dir_x * plane_len;
+    }
+
+    /// changes the resolution the game is drawn at, widening or narrowing the
+    /// field of view to match so the picture isn't stretched
+    pub fn set_internal_resolution(&mut self, width: i32, height: i32) -> Result<()> {
+        let old_aspect = self.renderer.width() as f32 / self.renderer.height() as f32;
+        self.renderer.set_internal_resolution(width, height)?;
+        let aspect = width as f32 / height as f32;
+        let plane_len = (self.fov_degrees.to_radians() / 2.0).tan() * aspect / old_aspect;
+        self.set_fov(plane_len.atan().to_degrees() * 2.0);
+        Ok(())
+    }
+
+    /// turns the debug tools on or off, leaving noclip and single stepping when turned off
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+        self.title_timer = TITLE_UPDATE_INTERVAL;
+        if !debug {
+            self.noclip = false;
+            self.single_step = false;
+            self.pending_title = Some(TITLE.to_owned());
+        }
+    }
+
+    /// vertical offset of the horizon in pixels for a screen of the given height
+    fn horizon_offset(&self, height: i32) -> i32 {
+        let bob = self.bob_phase.sin() * self.bob_amplitude * self.bob_blend;
+        ((self.pitch + bob) * height as f32) as i32
+    }
+
+    /// height of the given wall tile, walls are full height unless specified
+    fn wall_height(&self, tile: u32) -> f32 {
+        self.wall_heights.get(&tile).copied().unwrap_or(1.0)
+    }
+
+    /// tile value that the ray hit, rays that leave the map hit a plain wall
+    fn ray_tile(&self, ray: &Ray) -> u32 {
+        // rays that leave the map are stopped as if they hit a plain wall
+        if self.walls.contains(ray.map_x, ray.map_y) {
+            self.walls.tile_at(ray.map_x, ray.map_y)
+        } else {
+            1
+        }
+    }
+
+    /// screen row the wall hit by the ray starts at, how many rows it covers,
+    /// and how many rows a full height wall at the same distance would cover
+    fn wall_span(&self, ray: &Ray, height: i32, horizon: i32) -> (i32, i32, i32) {
+        // ceiling the line height mostly removes an issue where there
+        // will be a pixel of the floor/roof at the edges of the wall
+        let line_height = (height as f32 / ray.ray_dist).ceil() as i32;
+        // walls that aren't full height still sit on the floor
+        let wall_height = (line_height as f32 * self.wall_height(self.ray_tile(ray))).ceil() as i32;
+        let top =
+            ((horizon * 2 - line_height) as f32 / 2.0).ceil() as i32 + line_height - wall_height;
+        (top, wall_height, line_height)
+    }
+
+    /// fog color with its alpha set to how much fog covers the given distance
+    fn fog_overlay(&self, dist: f32) -> [u8; 4] {
+        let amount = fog_amount(dist, self.fog_density);
+        [
+            self.fog_color[0],
+            self.fog_color[1],
+            self.fog_color[2],
+            (amount * 255.0) as u8,
+        ]
+    }
+
+    /// replaces the contents of `lights` with every light shining this frame,
+    /// including the ones that follow the player
+    fn active_lights(&self, lights: &mut Vec<Light>) {
+        let player_light = self.player_light.map(|light| Light {
+            x_pos: self.player_x,
+            y_pos: self.player_y,
+            ..light
+        });
+        // the flash fades out linearly over its duration
+        let muzzle_flash = (self.muzzle_flash_timer > 0.0).then(|| {
+            let brightness =
+                self.muzzle_flash_intensity * self.muzzle_flash_timer / self.muzzle_flash_duration;
+            Light {
+                x_pos: self.player_x,
+                y_pos: self.player_y,
+                radius: MUZZLE_FLASH_RADIUS,
+                color: [brightness, brightness * 0.8, brightness * 0.5],
+            }
+        });
+
+        lights.clear();
+        lights.extend(
+            self.lights
+                .iter()
+                .copied()
+                .chain(player_light)
+                .chain(muzzle_flash),
+        );
+    }
+
+    /// plays the sound effect with the given name, does nothing without the `audio` feature
+    fn play_sound(&self, name: &str) {
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &self.audio {
+            if let Some(id) = audio.sound_id(name) {
+                if let Err(err) = audio.play(id) {
+                    eprintln!("failed to play sound {name}: {err:#}");
+                }
+            }
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = name;
+    }
+
+    /// plays the sound effect with the given name as if it came from the given position
+    fn play_sound_at(&self, name: &str, x: f32, y: f32) {
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &self.audio {
+            if let Some(id) = audio.sound_id(name) {
+                let dir = (self.dir_x, self.dir_y);
+                if let Err(err) = audio.play_at(id, x, y, self.player_x, self.player_y, dir) {
+                    eprintln!("failed to play sound {name}: {err:#}");
+                }
+            }
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = (name, x, y);
+    }
+
+    /// Update the `World` internal state; move player and projectiles
+    pub fn update(&mut self) {
+        if let Some(size) = self.input_manager.request_resize {
+            // the old surface keeps being drawn to if it can't be resized
+            if let Err(err) = self.renderer.resize(size) {
+                eprintln!("{err:#}");
+            }
+        }
+
+        // the game is frozen while paused, only drawing continues
+        if self.paused {
+            return;
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F1) {
+            self.set_debug(!self.debug);
+        }
+        if self.debug {
+            if self.input_manager.is_just_pressed(VirtualKeyCode::F6) {
+                self.single_step = !self.single_step;
+            }
+            let shift = self.input_manager.is_down(VirtualKeyCode::LShift)
+                || self.input_manager.is_down(VirtualKeyCode::RShift);
+            if self.single_step
+                && shift
+                && self.input_manager.is_just_pressed(VirtualKeyCode::Period)
+            {
+                self.step_requested = true;
+            }
+        }
+        // like pausing but the game can be advanced a frame at a time
+        if self.single_step && !std::mem::take(&mut self.step_requested) {
+            return;
+        }
+
+        let delta = self.input_manager.elapsed().unwrap().as_secs_f32();
+        self.advance(delta);
+    }
+
+    /// moves the game forward by the given number of seconds using the current input
+    fn advance(&mut self, delta: f32) {
+        self.animation_time += delta;
+        self.muzzle_flash_timer = (self.muzzle_flash_timer - delta).max(0.0);
+        self.recoil_timer = (self.recoil_timer - delta).max(0.0);
+        if let Some(flash) = &mut self.screen_flash {
+            flash.timer -= delta;
+            if flash.timer <= 0.0 {
+                self.screen_flash = None;
+            }
+        }
+
+        let turn_speed = {
+            let (motion_x, _) = self.input_manager.mouse_motion();
+            // exponential smoothing, decays towards zero on frames with no motion
+            self.smoothed_motion_x +=
+                (motion_x as f32 - self.smoothed_motion_x) * self.mouse_smoothing.clamp(0.0, 1.0);
+            if self.smoothed_motion_x.abs() < 1e-3 {
+                self.smoothed_motion_x = 0.0;
+            }
+            let (look_x, _) = self.input_manager.gamepad_look_axis();
+            let mut keys = 0.0;
+            if self.input_manager.is_action_down(Action::TurnRight) {
+                keys += 1.0;
+            }
+            if self.input_manager.is_action_down(Action::TurnLeft) {
+                keys -= 1.0;
+            }
+            // mouse motion is already the distance moved over the whole frame so
+            // it isn't scaled by delta, doing so would make turning slower at high
+            // frame rates. the stick and keys are rates of turning so they do need delta
+            self.smoothed_motion_x * self.mouse_sensitivity
+                + look_x * delta * GAMEPAD_TURN_SPEED
+                + keys * delta * self.keyboard_turn_speed
+        };
+
+        // looking up and down moves the horizon rather than truly rotating the camera
+        let (_, motion_y) = self.input_manager.mouse_motion();
+        let (_, look_y) = self.input_manager.gamepad_look_axis();
+        self.pitch = (self.pitch - motion_y as f32 * self.mouse_sensitivity
+            + look_y * delta * GAMEPAD_PITCH_SPEED)
+            .clamp(-MAX_PITCH, MAX_PITCH);
+
+        let old_dir_x = self.dir_x;
+        self.dir_x = self.dir_x * (turn_speed).cos() - self.dir_y * (turn_speed).sin();
+        self.dir_y = old_dir_x * (turn_speed).sin() + self.dir_y * (turn_speed).cos();
+        let old_plane_x = self.plane_x;
+        self.plane_x = self.plane_x * (turn_speed).cos() - self.plane_y * (turn_speed).sin();
+        self.plane_y = old_plane_x * (turn_speed).sin() + self.plane_y * (turn_speed).cos();
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::LBracket) {
+            self.set_fov(self.fov_degrees - FOV_STEP);
+        }
+        if self.input_manager.is_just_pressed(VirtualKeyCode::RBracket) {
+            self.set_fov(self.fov_degrees + FOV_STEP);
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F3) {
+            self.show_fps = !self.show_fps;
+            self.fps_timer = 0.0;
+            if !self.show_fps && !self.debug {
+                self.pending_title = Some(TITLE.to_owned());
+            }
+        }
+        // the debug title already includes the fps
+        if self.show_fps && !self.debug {
+            self.fps_timer += delta;
+            if self.fps_timer >= FPS_TITLE_INTERVAL {
+                self.fps_timer -= FPS_TITLE_INTERVAL;
+                self.pending_title = Some(format!("{TITLE} - {:.1} fps", self.input_manager.fps()));
+            }
+        }
+        if self.debug {
+            self.title_timer += delta;
+            if self.title_timer >= TITLE_UPDATE_INTERVAL {
+                self.title_timer -= TITLE_UPDATE_INTERVAL;
+                self.pending_title = Some(format!(
+                    "{TITLE} - {:.1} fps - x: {:.2} y: {:.2} dir: {:.0}",
+                    self.input_manager.fps(),
+                    self.player_x,
+                    self.player_y,
+                    self.dir_y.atan2(self.dir_x).to_degrees(),
+                ));
+            }
+        }
+
+        // scrolling up selects the previous weapon and down selects the next
+        self.weapon_scroll += self.input_manager.scroll_delta();
+        let notches = self.weapon_scroll.trunc();
+        if notches != 0.0 {
+            self.weapon_scroll -= notches;
+            let index = WEAPONS
+                .iter()
+                .position(|&weapon| weapon == self.current_weapon)
+                .unwrap_or(0);
+            self.current_weapon =
+                WEAPONS[(index as i32 - notches as i32).rem_euclid(WEAPONS.len() as i32) as usize];
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F4) {
+            let mode = match self.renderer.scaling_mode() {
+                ScalingMode::PreserveAspect => ScalingMode::IntegerOnly,
+                ScalingMode::IntegerOnly => ScalingMode::Stretch,
+                ScalingMode::Stretch => ScalingMode::PreserveAspect,
+            };
+            self.renderer.set_scaling_mode(mode);
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F12) {
+            if let Err(err) = self.screenshot() {
+                eprintln!("screenshot failed: {err:#}");
+            }
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F5) {
+            if let Err(err) = self.save(QUICKSAVE_PATH) {
+                eprintln!("quick-save failed: {err:#}");
+            }
+        }
+        if self.input_manager.is_just_pressed(VirtualKeyCode::F9) {
+            if let Err(err) = self.load(QUICKSAVE_PATH) {
+                eprintln!("quick-load failed: {err:#}");
+            }
+        }
+
+        if self.input_manager.is_just_pressed(VirtualKeyCode::M) {
+            self.show_minimap = !self.show_minimap;
+        }
+        if self.debug && self.input_manager.is_just_pressed(VirtualKeyCode::N) {
+            self.noclip = !self.noclip;
+        }
+
+        let move_speed = if self.input_manager.is_action_down(Action::Sprint) {
+            self.run_speed
+        } else {
+            self.walk_speed
+        } * delta;
+
+        let (mut move_x, mut move_y) = self.movement_input();
+        move_x *= move_speed;
+        move_y *= move_speed;
+
+        let moved = (move_x.powi(2) + move_y.powi(2)).sqrt();
+        if moved > 0.0 {
+            self.bob_phase = (self.bob_phase + moved * self.bob_frequency) % std::f32::consts::TAU;
+            self.bob_blend = (self.bob_blend + delta * BOB_EASE_SPEED).min(1.0);
+        } else {
+            self.bob_blend = (self.bob_blend - delta * BOB_EASE_SPEED).max(0.0);
+        }
+
+        (self.player_x, self.player_y) = if self.noclip {
+            (self.player_x + move_x, self.player_y + move_y)
+        } else {
+            try_move(
+                &self.walls,
+                self.player_x,
+                self.player_y,
+                move_x,
+                move_y,
+                PLAYER_RADIUS,
+            )
+        };
+
+        if self.input_manager.is_action_just_pressed(Action::Use) {
+            self.push_wall();
+        }
+        for push_wall in &mut self.push_walls {
+            push_wall.progress = (push_wall.progress + delta * PUSH_WALL_SPEED).min(1.0);
+        }
+        // finished walls leave their old tile open and stay put as a normal wall
+        let walls = &mut self.walls;
+        let spent = &mut self.spent_push_walls;
+        self.push_walls.retain(|push_wall| {
+            if push_wall.progress < 1.0 {
+                return true;
+            }
+            Arc::make_mut(walls).set_tile(push_wall.x, push_wall.y, 0);
+            spent.insert((push_wall.x + push_wall.dir_x, push_wall.y + push_wall.dir_y));
+            false
+        });
+
+        // walking over a pickup collects it, health is left alone while already at full health
+        let (player_x, player_y) = (self.player_x, self.player_y);
+        let full_health = self.health >= PLAYER_MAX_HEALTH;
+        let mut collected = Vec::new();
+        self.entities.retain(|entity| {
+            let EntityType::Pickup { kind } = entity.entity_type else {
+                return true;
+            };
+            let in_reach = (player_x - entity.x_pos).powi(2) + (player_y - entity.y_pos).powi(2)
+                <= PICKUP_DIST_SQ;
+            if !in_reach || (kind == PickupKind::Health && full_health) {
+                return true;
+            }
+            collected.push(kind);
+            false
+        });
+        if !collected.is_empty() {
+            self.screen_flash = Some(self.pickup_flash);
+        }
+        for kind in collected {
+            match kind {
+                PickupKind::Health => {
+                    self.health = (self.health + HEALTH_PICKUP_AMOUNT).min(PLAYER_MAX_HEALTH);
+                }
+                PickupKind::Ammo => self.ammo += AMMO_PICKUP_AMOUNT,
+            }
+        }
+
+        self.fire_cooldown = (self.fire_cooldown - delta).max(0.0);
+        let trigger = if self.automatic {
+            self.input_manager.is_action_down(Action::Shoot)
+                || self
+                    .input_manager
+                    .is_gamepad_down(GamepadButton::RightTrigger)
+        } else {
+            self.input_manager.is_action_just_pressed(Action::Shoot)
+                || self
+                    .input_manager
+                    .is_gamepad_just_pressed(GamepadButton::RightTrigger)
+        };
+        let shoot = trigger && self.fire_cooldown <= 0.0;
+        if shoot && self.fire_rate > 0.0 {
+            self.fire_cooldown = 1.0 / self.fire_rate;
+        }
+        if shoot && self.ammo == 0 && !self.infinite_ammo {
+            self.play_sound("empty");
+        } else if shoot {
+            if !self.infinite_ammo {
+                self.ammo -= 1;
+            }
+            self.muzzle_flash_timer = self.muzzle_flash_duration;
+            self.recoil_timer = self.recoil_duration;
+            self.play_sound("shoot");
+            match self.current_weapon {
+                // spawned ahead of the player so it starts clear of them
+                WeaponKind::Projectile => self.entities.push(
+                    Entity::new(
+                        self.player_x + self.dir_x * PROJECTILE_SPAWN_OFFSET,
+                        self.player_y + self.dir_y * PROJECTILE_SPAWN_OFFSET,
+                        5,
+                        EntityType::Projectile(self.dir_x * 8.0, self.dir_y * 8.0, Owner::Player),
+                    )
+                    .with_animation(PROJECTILE_FRAMES.to_vec(), PROJECTILE_FRAME_TIME),
+                ),
+                WeaponKind::Hitscan => self.fire_hitscan(),
+            }
+        }
+
+        let mut enemy_shots = Vec::new();
+        for entity in self.entities.iter_mut() {
+            match entity.entity_type {
+                EntityType::Projectile(x_vel, y_vel, _) => {
+                    entity.x_pos += x_vel * delta;
+                    entity.y_pos += y_vel * delta;
+                }
+                EntityType::Enemy { speed, .. } => {
+                    let to_player_x = self.player_x - entity.x_pos;
+                    let to_player_y = self.player_y - entity.y_pos;
+                    let dist = (to_player_x.powi(2) + to_player_y.powi(2)).sqrt();
+                    let sees_player = line_of_sight(
+                        &self.walls,
+                        entity.x_pos,
+                        entity.y_pos,
+                        self.player_x,
+                        self.player_y,
+                    );
+
+                    if sees_player && dist > 0.0 {
+                        entity.attack_timer -= delta;
+                        if entity.attack_timer <= 0.0 {
+                            entity.attack_timer = ENEMY_FIRE_INTERVAL;
+                            let (aim_x, aim_y) = (to_player_x / dist, to_player_y / dist);
+                            // spawned ahead of the enemy so it starts clear of them
+                            enemy_shots.push(
+                                Entity::new(
+                                    entity.x_pos + aim_x * (ENEMY_RADIUS + 0.1),
+                                    entity.y_pos + aim_y * (ENEMY_RADIUS + 0.1),
+                                    5,
+                                    EntityType::Projectile(
+                                        aim_x * ENEMY_PROJECTILE_SPEED,
+                                        aim_y * ENEMY_PROJECTILE_SPEED,
+                                        Owner::Enemy,
+                                    ),
+                                )
+                                .with_animation(PROJECTILE_FRAMES.to_vec(), PROJECTILE_FRAME_TIME),
+                            );
+                        }
+                    } else {
+                        // spotting the player again gives them a moment to react
+                        entity.attack_timer = ENEMY_FIRE_INTERVAL;
+                    }
+
+                    // stop once close enough instead of walking into the player
+                    if dist > ENEMY_STOP_DIST {
+                        entity.repath_timer -= delta;
+                        // walk straight at a visible player, otherwise follow a path around the walls
+                        let target = if sees_player {
+                            entity.path.clear();
+                            Some((self.player_x, self.player_y))
+                        } else {
+                            // paths are only recomputed every so often since it's expensive
+                            if entity.repath_timer <= 0.0 {
+                                entity.repath_timer = PATH_RECOMPUTE_INTERVAL;
+                                let start = (entity.x_pos as i32, entity.y_pos as i32);
+                                let goal = (self.player_x as i32, self.player_y as i32);
+                                entity.path = pathfind(&self.walls, start, goal)
+                                    .map(|path| path.into_iter().skip(1).collect())
+                                    .unwrap_or_default();
+                            }
+                            if let Some(&(tile_x, tile_y)) = entity.path.first() {
+                                if (tile_x as f32 + 0.5 - entity.x_pos).abs() < PATH_WAYPOINT_DIST
+                                    && (tile_y as f32 + 0.5 - entity.y_pos).abs()
+                                        < PATH_WAYPOINT_DIST
+                                {
+                                    entity.path.remove(0);
+                                }
+                            }
+                            entity
+                                .path
+                                .first()
+                                .map(|&(tile_x, tile_y)| (tile_x as f32 + 0.5, tile_y as f32 + 0.5))
+                        };
+
+                        if let Some((target_x, target_y)) = target {
+                            let to_target_x = target_x - entity.x_pos;
+                            let to_target_y = target_y - entity.y_pos;
+                            let target_dist = (to_target_x.powi(2) + to_target_y.powi(2)).sqrt();
+                            // don't overshoot the center of a path tile
+                            let step = (speed * delta).min(target_dist);
+                            if target_dist > 0.0 {
+                                entity.facing = to_target_y.atan2(to_target_x);
+                                (entity.x_pos, entity.y_pos) = try_move(
+                                    &self.walls,
+                                    entity.x_pos,
+                                    entity.y_pos,
+                                    to_target_x / target_dist * step,
+                                    to_target_y / target_dist * step,
+                                    ENEMY_RADIUS,
+                                );
+                            }
+                        }
+                    }
+                }
+                EntityType::Stationary | EntityType::Pickup { .. } => (),
+            }
+
+            if let Some(animation) = &mut entity.animation {
+                animation.elapsed += delta;
+                // wrap so the timer doesn't lose precision over long play sessions
+                let loop_time = animation.frame_time * animation.frames.len() as f32;
+                if loop_time > 0.0 {
+                    animation.elapsed %= loop_time;
+                }
+            }
+        }
+
+        self.entities.extend(enemy_shots);
+
+        // entities are only flagged here and removed afterwards so that
+        // removing a hit target never shifts indices still being checked
+        let mut removed = vec![false; self.entities.len()];
+        let mut damage_taken = 0;
+        let mut enemy_hits = Vec::new();
+        let mut impacts = Vec::new();
+        // positions are in tiles so entities are bounded by the map, not the screen
+        let map_width = self.walls.width() as f32;
+        let map_height = self.walls.height() as f32;
+        for (i, entity) in self.entities.iter().enumerate() {
+            if entity.x_pos < 0.0
+                || entity.x_pos >= map_width
+                || entity.y_pos < 0.0
+                || entity.y_pos >= map_height
+                || self
+                    .walls
+                    .is_solid(entity.x_pos as i32, entity.y_pos as i32)
+            {
+                removed[i] = true;
+                // step back out of the wall so the burst isn't hidden behind it
+                if let EntityType::Projectile(x_vel, y_vel, _) = entity.entity_type {
+                    impacts.push((entity.x_pos - x_vel * delta, entity.y_pos - y_vel * delta));
+                }
+                continue;
+            }
+
+            let EntityType::Projectile(_, _, owner) = entity.entity_type else {
+                continue;
+            };
+
+            if owner != Owner::Player {
+                if (self.player_x - entity.x_pos).powi(2) + (self.player_y - entity.y_pos).powi(2)
+                    <= PROJECTILE_HIT_DIST_SQ
+                {
+                    removed[i] = true;
+                    damage_taken += PROJECTILE_DAMAGE;
+                }
+                continue;
+            }
+
+            let target = self.entities.iter().enumerate().position(|(j, other)| {
+                !removed[j]
+                    && matches!(
+                        other.entity_type,
+                        EntityType::Stationary | EntityType::Enemy { .. }
+                    )
+                    && (other.x_pos - entity.x_pos).powi(2) + (other.y_pos - entity.y_pos).powi(2)
+                        <= PROJECTILE_HIT_DIST_SQ
+            });
+            if let Some(j) = target {
+                removed[i] = true;
+                if let EntityType::Enemy { .. } = self.entities[j].entity_type {
+                    enemy_hits.push(j);
+                } else {
+                    removed[j] = true;
+                }
+            }
+        }
+
+        for j in enemy_hits {
+            if let EntityType::Enemy { health, .. } = &mut self.entities[j].entity_type {
+                *health -= PROJECTILE_DAMAGE;
+                if *health <= 0 {
+                    removed[j] = true;
+                }
+            }
+        }
+
+        let mut removed = removed.into_iter();
+        self.entities.retain(|_| !removed.next().unwrap_or(false));
+
+        for particle in self.particles.iter_mut() {
+            particle.x_pos += particle.x_vel * delta;
+            particle.y_pos += particle.y_vel * delta;
+            particle.lifetime -= delta;
+        }
+        self.particles.retain(|p| p.lifetime > 0.0);
+        for (x, y) in impacts {
+            self.spawn_impact(x, y);
+            self.play_sound_at("impact", x, y);
+        }
+
+        if damage_taken > 0 {
+            self.damage(damage_taken);
+        }
+    }
+
+    /// direction the held movement inputs push the player in, never longer than one
+    fn movement_input(&self) -> (f32, f32) {
+        let mut move_x = 0.0;
+        let mut move_y = 0.0;
+
+        if self.input_manager.is_action_down(Action::Forward) {
+            move_x += self.dir_x;
+            move_y += self.dir_y;
+        }
+        if self.input_manager.is_action_down(Action::Back) {
+            move_x -= self.dir_x;
+            move_y -= self.dir_y;
+        }
+        if self.input_manager.is_action_down(Action::StrafeRight) {
+            move_y += self.dir_x;
+            move_x -= self.dir_y;
+        }
+        if self.input_manager.is_action_down(Action::StrafeLeft) {
+            move_y -= self.dir_x;
+            move_x += self.dir_y;
+        }
+
+        let (stick_x, stick_y) = self.input_manager.gamepad_move_axis();
+        move_x += self.dir_x * stick_y - self.dir_y * stick_x;
+        move_y += self.dir_y * stick_y + self.dir_x * stick_x;
+
+        // combined inputs like forward and strafe are scaled back down to unit length so
+        // diagonals aren't faster, while a partly pushed stick keeps its shorter length.
+        // only dividing when longer than one also avoids normalizing a zero length vector
+        let dist = (move_x.powi(2) + move_y.powi(2)).sqrt();
+        if dist > 1.0 {
+            move_x /= dist;
+            move_y /= dist;
+        }
+        (move_x, move_y)
+    }
+
+    /// hits the closest entity in range straight ahead of the player, or marks
+    /// the wall behind it if nothing is in the way
+    fn fire_hitscan(&mut self) {
+        let wall = cast_ray(
+            &self.walls,
+            &self.wall_shapes,
+            &self.push_walls,
+            self.player_x,
+            self.player_y,
+            self.dir_x,
+            self.dir_y,
+        );
+        // the facing direction is unit length so distances along it are in tiles
+        let range = wall.ray_dist.min(HITSCAN_RANGE);
+
+        let target = self
+            .entities
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| {
+                matches!(
+                    entity.entity_type,
+                    EntityType::Stationary | EntityType::Enemy { .. }
+                )
+            })
+            .filter_map(|(i, entity)| {
+                let to_x = entity.x_pos - self.player_x;
+                let to_y = entity.y_pos - self.player_y;
+                // distance along the ray to the point closest to the entity
+                let along = to_x * self.dir_x + to_y * self.dir_y;
+                let miss_sq = to_x * to_x + to_y * to_y - along * along;
+                (along > 0.0 && along <= range && miss_sq <= HITSCAN_HIT_RADIUS.powi(2))
+                    .then_some((i, along))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        match target {
+            Some((i, _)) => {
+                if let EntityType::Enemy { health, .. } = &mut self.entities[i].entity_type {
+                    *health -= PROJECTILE_DAMAGE;
+                    if *health <= 0 {
+                        self.entities.remove(i);
+                    }
+                } else {
+                    self.entities.remove(i);
+                }
+            }
+            None if wall.ray_dist <= HITSCAN_RANGE => {
+                // pulled back slightly so the burst isn't hidden inside the wall
+                let dist = wall.ray_dist - PARTICLE_SIZE;
+                let x = self.player_x + self.dir_x * dist;
+                let y = self.player_y + self.dir_y * dist;
+                self.spawn_impact(x, y);
+                self.play_sound_at("impact", x, y);
+            }
+            None => (),
+        }
+    }
+
+    /// starts sliding the push wall the player is facing, if there's room behind it
+    fn push_wall(&mut self) {
+        // walls are pushed straight along whichever axis the player is mostly facing
+        let (dir_x, dir_y) = if self.dir_x.abs() > self.dir_y.abs() {
+            (self.dir_x.signum() as i32, 0)
+        } else {
+            (0, self.dir_y.signum() as i32)
+        };
+        let x = self.player_x as i32 + dir_x;
+        let y = self.player_y as i32 + dir_y;
+
+        let tile = self.walls.tile_at(x, y);
+        let moving = self
+            .push_walls
+            .iter()
+            .any(|push_wall| push_wall.covers(x, y));
+        // anything standing where the wall would end up would be trapped inside it
+        let (to_x, to_y) = (x + dir_x, y + dir_y);
+        let blocked = self.entities.iter().any(|entity| {
+            !matches!(entity.entity_type, EntityType::Projectile(..))
+                && (entity.x_pos - (to_x as f32 + 0.5)).abs() < 0.5 + ENEMY_RADIUS
+                && (entity.y_pos - (to_y as f32 + 0.5)).abs() < 0.5 + ENEMY_RADIUS
+        });
+        if !self.push_wall_tiles.contains(&tile)
+            || moving
+            || blocked
+            || self.spent_push_walls.contains(&(x, y))
+            || self.walls.is_solid(to_x, to_y)
+        {
+            return;
+        }
+
+        // the wall fills both tiles while it moves so nothing can walk into it
+        Arc::make_mut(&mut self.walls).set_tile(to_x, to_y, tile);
+        self.push_walls.push(PushWall {
+            x,
+            y,
+            dir_x,
+            dir_y,
+            progress: 0.0,
+        });
+    }
+
+    /// bursts a handful of particles outward from the given point
+    fn spawn_impact(&mut self, x: f32, y: f32) {
+        let room = MAX_PARTICLES.saturating_sub(self.particles.len());
+        for i in 0..IMPACT_PARTICLES.min(room) {
+            // spread evenly around the circle then nudged so the burst doesn't look like a perfect ring
+            let angle = (i as f32 + self.rng.gen_range(-0.5..0.5)) / IMPACT_PARTICLES as f32
+                * std::f32::consts::TAU;
+            let speed = PARTICLE_SPEED * self.rng.gen_range(0.6..=1.0);
+            self.particles.push(Particle {
+                x_pos: x,
+                y_pos: y,
+                x_vel: angle.cos() * speed,
+                y_vel: angle.sin() * speed,
+                lifetime: PARTICLE_LIFETIME,
+                color: [0xff, 0xc0, 0x40, 0xff],
+            });
+        }
+    }
+
+    /// saves the last drawn frame to a png named after the current time
+    fn screenshot(&self) -> Result<()> {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = format!("./screenshot_{millis}.png");
+        self.renderer
+            .capture()
+            .save(&path)
+            .with_context(|| format!("failed to save screenshot to {path}"))
+    }
+
+    /// writes the player and entities to the given file
+    fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let state = SaveState {
+            map_name: self.map_name.clone(),
+            player_x: self.player_x,
+            player_y: self.player_y,
+            dir_x: self.dir_x,
+            dir_y: self.dir_y,
+            entities: self.entities.clone(),
+        };
+        let contents = ron::ser::to_string_pretty(&state, ron::ser::PrettyConfig::default())?;
+        fs::write(path.as_ref(), contents)
+            .with_context(|| format!("failed to write save to {}", path.as_ref().display()))
+    }
+
+    /// restores the player and entities from the given file
+    fn load(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let contents = fs::read_to_string(path.as_ref())
+            .with_context(|| format!("failed to read save from {}", path.as_ref().display()))?;
+        let state: SaveState = ron::from_str(&contents)?;
+
+        if state.map_name != self.map_name {
+            bail!(
+                "save is for map \"{}\" but \"{}\" is loaded",
+                state.map_name,
+                self.map_name
+            );
+        }
+        for entity in &state.entities {
+            let frames = entity.animation.iter().flat_map(|a| a.frames.iter());
+            let rotations = entity.rotations.iter().flatten();
+            if let Some(id) = std::iter::once(&entity.texture_id)
+                .chain(frames)
+                .chain(rotations)
+                .find(|&&id| id >= self.textures.len())
+            {
+                bail!("save references missing texture {id}");
+            }
+        }
+
+        self.player_x = state.player_x;
+        self.player_y = state.player_y;
+        self.dir_x = state.dir_x;
+        self.dir_y = state.dir_y;
+        self.set_fov(self.fov_degrees);
+        self.entities = state.entities;
+        self.particles.clear();
+        Ok(())
+    }
+
+    /// reduces the player's health, never going below zero
+    pub fn damage(&mut self, amount: i32) {
+        self.health = (self.health - amount).max(0);
+        self.screen_flash = Some(self.hurt_flash);
+    }
+
+    /// returns whether or not the player has run out of health
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
+
+    pub fn render(&self) -> Result<()> {
+        // there's no surface to present to while minimized
+        if self.input_manager.minimized {
+            return Ok(());
+        }
+        self.renderer.render()
+    }
+
+    /// Draw the `World` state to the frame buffer.
+    pub fn draw(&mut self) {
+        if self.input_manager.minimized {
+            return;
+        }
+        // the last frame is refilled rather than building a new one each time
+        let mut frame = std::mem::take(&mut self.frame);
+        self.render_scene_into(&mut frame);
+        frame.apply(&mut self.renderer, &self.textures);
+        self.frame = frame;
+    }
+
+    /// id of the texture drawn for ids that don't have one, if one has been pushed.
+    /// a generated checkerboard is drawn for them otherwise
+    pub fn missing_texture_id(&self) -> Option<usize> {
+        self.textures.missing_texture_id()
+    }
+
+    /// distance to the wall in the given screen column as of the last draw, anything
+    /// farther away is hidden behind it. infinite for columns that haven't been drawn
+    pub fn depth_at_column(&self, x: i32) -> f32 {
+        usize::try_from(x)
+            .ok()
+            .and_then(|x| self.frame.z_buffer.get(x))
+            .map_or(f32::INFINITY, |ray| ray.ray_dist)
+    }
+
+    /// works out everything drawn this frame without touching the frame buffer
+    pub fn render_scene(&self) -> FrameCommands {
+        let mut frame = FrameCommands::default();
+        self.render_scene_into(&mut frame);
+        frame
+    }
+
+    /// same as `render_scene` but fills in an existing frame, reusing its buffers
+    pub fn render_scene_into(&self, frame: &mut FrameCommands) {
+        let width = self.renderer.width();
+        let height = self.renderer.height();
+        // screen row that the camera looks straight ahead at
+        let horizon = height / 2 + self.horizon_offset(height);
+        let commands = &mut frame.commands;
+        commands.clear();
+        commands.push(DrawCommand::Fill([0, 0, 0, 0xff]));
+
+        // cast a ray for each pixel column. only the fields the rays need are
+        // borrowed since the whole app can't be shared between threads
+        let (walls, wall_shapes, push_walls) = (&self.walls, &self.wall_shapes, &self.push_walls);
+        let (player_x, player_y) = (self.player_x, self.player_y);
+        let (dir_x, dir_y, plane_x, plane_y) = (self.dir_x, self.dir_y, self.plane_x, self.plane_y);
+        let z_buffer = &mut frame.z_buffer;
+        z_buffer.resize(width.max(0) as usize, Ray::default());
+        let cast_column = |(x, column): (usize, &mut Ray)| {
+            let camera_x = 2.0 * x as f32 / width as f32 - 1.0;
+            let ray_dir_x = dir_x + plane_x * -camera_x;
+            let ray_dir_y = dir_y + plane_y * -camera_x;
+            let mut ray = cast_ray(
+                walls,
+                wall_shapes,
+                push_walls,
+                player_x,
+                player_y,
+                ray_dir_x,
+                ray_dir_y,
+            );
+            ray.ray_dist = ray.ray_dist.max(NEAR_CLIP);
+            *column = ray;
+        };
+        if self.parallel_raycast {
+            z_buffer.par_iter_mut().enumerate().for_each(cast_column);
+        } else {
+            z_buffer.iter_mut().enumerate().for_each(cast_column);
+        }
+
+        if let SkyMode::Gradient {
+            zenith,
+            horizon: horizon_color,
+        } = self.sky_mode
+        {
+            commands.push(DrawCommand::VerticalGradient {
+                top_color: zenith,
+                bottom_color: horizon_color,
+                y0: 0,
+                y1: horizon,
+            });
+        }
+        if let SkyMode::Texture(sky_id) = self.sky_mode {
+            let sky = self.textures.texture_or_fallback(sky_id);
+            for x in 0..width {
+                let camera_x = 2.0 * x as f32 / width as f32 - 1.0;
+                let ray_dir_x = self.dir_x + self.plane_x * -camera_x;
+                let ray_dir_y = self.dir_y + self.plane_y * -camera_x;
+
+                // map the ray's angle around the player onto the width of the sky
+                let angle = ray_dir_y.atan2(ray_dir_x) / std::f32::consts::TAU + 0.5;
+                commands.push(DrawCommand::TextureColumn {
+                    texture: sky_id,
+                    tint: [0xff, 0xff, 0xff, 0xff],
+                    overlay: [0, 0, 0, 0],
+                    x,
+                    y: 0,
+                    height: horizon.max(0) as u32,
+                    tex_x: angle * sky.width() as f32,
+                });
+            }
+        }
+
+        let floor = &mut frame.floor;
+        floor.floor = Arc::clone(&self.floor);
+        floor.ceiling = Arc::clone(&self.ceiling);
+        floor.horizon = horizon;
+        floor.player_x = self.player_x;
+        floor.player_y = self.player_y;
+        floor.ray_dir_left = (self.dir_x + self.plane_x, self.dir_y + self.plane_y);
+        floor.ray_dir_right = (self.dir_x - self.plane_x, self.dir_y - self.plane_y);
+        floor.wall_rows.clear();
+        floor.wall_rows.extend(z_buffer.iter().map(|ray| {
+            let (top, wall_height, _) = self.wall_span(ray, height, horizon);
+            top..top + wall_height
+        }));
+        floor.skip_ceiling = self.sky_mode != SkyMode::Ceiling;
+        self.active_lights(&mut floor.lights);
+        floor.filtering = self.renderer.filtering;
+        floor.fog_color = self.fog_color;
+        floor.fog_density = self.fog_density;
+        commands.push(DrawCommand::Floor);
+        let lights = &floor.lights;
+
+        for (x, ray) in z_buffer.iter().enumerate() {
+            let ray_dir_x = ray.ray_dir_x;
+            let ray_dir_y = ray.ray_dir_y;
+            let perp_wall_dist = ray.ray_dist;
+            let side = ray.side;
+            let wall_x = ray.wall_x;
+
+            let tile = self.ray_tile(ray);
+            let texture_id = match self.wall_animations.get(&tile) {
+                Some((frames, frame_time)) if !frames.is_empty() && *frame_time > 0.0 => {
+                    let frame = (self.animation_time / frame_time) as usize;
+                    frames[frame % frames.len()]
+                }
+                _ => self
+                    .wall_textures
+                    .get(&tile)
+                    .copied()
+                    .unwrap_or_else(|| WallTextures::from(tile))
+                    .face(side, ray_dir_x, ray_dir_y),
+            };
+            let texture = self.textures.texture_or_fallback(texture_id);
+
+            let mut tex_x = wall_x * texture.width() as f32;
+            // unmirrors texture on certain walls
+            if (side == 0 && ray_dir_x < 0.0) || (side == 1 && ray_dir_y > 0.0) {
+                tex_x = texture.width() as f32 - tex_x;
+            }
+
+            let (top, wall_height, line_height) = self.wall_span(ray, height, horizon);
+            let shade =
+                self.side_shade[side as usize] * self.ambient * line_height as f32 / height as f32;
+            let light = light_at(
+                lights,
+                self.player_x + ray_dir_x * perp_wall_dist,
+                self.player_y + ray_dir_y * perp_wall_dist,
+            );
+            let [r, g, b] = lit_brightness(shade, light).map(|channel| (channel * 255.0) as u8);
+            let tint = [r, g, b, 0xff];
+
+            commands.push(DrawCommand::TextureColumn {
+                texture: texture_id,
+                tint,
+                overlay: self.fog_overlay(perp_wall_dist),
+                x: x as i32,
+                y: top,
+                height: wall_height as u32,
+                tex_x,
+            });
+        }
+
+        let distance = self
+            .entities
+            .iter()
+            .map(|e| (self.player_x - e.x_pos).powi(2) + (self.player_y - e.y_pos).powi(2));
+        // entities too far away to be worth drawing are skipped entirely
+        let sprite_order = &mut frame.sprite_order;
+        sprite_order.clear();
+        sprite_order.extend(
+            (0..self.entities.len())
+                .zip(distance)
+                .filter(|(_, dist)| *dist <= self.max_sprite_dist_sq),
+        );
+
+        // sort farthest entity first
+        sprite_order.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        for index in sprite_order.iter().map(|(i, _)| *i) {
+            let entity = &self.entities[index];
+            commands.push(DrawCommand::Sprite {
+                texture: entity.current_texture(self.player_x, self.player_y),
+                world_x: entity.x_pos,
+                world_y: entity.y_pos,
+                scale: entity.scale,
+                v_offset: entity.v_offset,
+            });
+        }
+
+        for particle in &self.particles {
+            commands.push(DrawCommand::Particle {
+                color: particle.color,
+                world_x: particle.x_pos,
+                world_y: particle.y_pos,
+                size: PARTICLE_SIZE,
+            });
+        }
+
+        if let Some(flash) = &self.screen_flash {
+            commands.push(DrawCommand::Vignette(flash.current_color()));
+        }
+
+        if let Some(texture_id) = self.viewmodel_texture {
+            let texture = self.textures.texture_or_fallback(texture_id);
+            let view_height = (height as f32 * VIEWMODEL_SIZE) as i32;
+            let view_width = view_height * texture.width() as i32 / texture.height().max(1) as i32;
+
+            // swings side to side once per step and dips at the end of each swing
+            let sway_x = self.bob_phase.cos() * VIEWMODEL_SWAY * self.bob_blend * width as f32;
+            let sway_y =
+                self.bob_phase.sin().abs() * VIEWMODEL_SWAY * self.bob_blend * width as f32;
+            // kicks up and eases back down over the recoil
+            let recoil = if self.recoil_duration > 0.0 {
+                let progress = 1.0 - self.recoil_timer / self.recoil_duration;
+                (progress * std::f32::consts::PI).sin() * VIEWMODEL_RECOIL * height as f32
+            } else {
+                0.0
+            };
+
+            commands.push(DrawCommand::SubTexture {
+                texture: texture_id,
+                tint: [0xff, 0xff, 0xff, 0xff],
+                overlay: [0, 0, 0, 0],
+                x: (width - view_width) / 2 + sway_x as i32,
+                y: height - view_height + sway_y as i32 - recoil as i32,
+                size: PhysicalSize::new(view_width.max(0) as u32, view_height.max(0) as u32),
+                sub_image: Rect {
+                    x: 0,
+                    y: 0,
+                    width: texture.width(),
+                    height: texture.height(),
+                },
+                blended: true,
+            });
+        }
+
+        commands.push(DrawCommand::Crosshair {
+            color: [0xff, 0xff, 0xff, 0xff],
+            center_x: width / 2,
+            center_y: height / 2,
+            size: CROSSHAIR_SIZE,
+            gap: CROSSHAIR_GAP,
+        });
+
+        if self.show_minimap {
+            frame.minimap_walls = Arc::clone(&self.walls);
+            commands.push(DrawCommand::Minimap {
+                origin_x: MINIMAP_CELL_SIZE,
+                origin_y: MINIMAP_CELL_SIZE,
+                cell_size: MINIMAP_CELL_SIZE,
+                radius: MINIMAP_RADIUS,
+            });
+        }
+
+        if self.paused {
+            commands.push(DrawCommand::FillBlended([0, 0, 0, 0x99]));
+        }
+
+        let mut depth = std::mem::take(&mut frame.camera.depth);
+        depth.clear();
+        depth.extend(z_buffer.iter().map(|ray| ray.ray_dist));
+        frame.camera = Camera {
+            x: self.player_x,
+            y: self.player_y,
+            dir_x: self.dir_x,
+            dir_y: self.dir_y,
+            plane_x: self.plane_x,
+            plane_y: self.plane_y,
+            horizon,
+            depth,
+            near_clip: NEAR_CLIP,
+            fog_color: self.fog_color,
+            fog_density: self.fog_density,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::DynamicImage;
+    use input::tests::{key, mouse, new_frame};
+    use winit::event::{ElementState, Event, MouseButton};
+
+    /// the default map drawn into memory, without any textures loaded
+    fn test_app() -> App {
+        App::new(Renderer::headless(WIDTH, HEIGHT), InputManager::new(), 0)
+    }
+
+    /// places the player in the middle of the given tile facing along the given direction
+    fn place(app: &mut App, x: f32, y: f32, dir_x: f32, dir_y: f32) {
+        app.player_x = x;
+        app.player_y = y;
+        app.dir_x = dir_x;
+        app.dir_y = dir_y;
+        app.set_fov(app.fov_degrees);
+    }
+
+    /// pulls the trigger for a single frame
+    fn shoot(app: &mut App) {
+        app.input_manager.process_event(&new_frame());
+        app.input_manager
+            .process_event(&mouse(MouseButton::Left, ElementState::Pressed));
+        app.advance(0.0);
+        app.input_manager
+            .process_event(&mouse(MouseButton::Left, ElementState::Released));
+        app.input_manager.process_event(&new_frame());
+    }
+
+    /// finishes the frame started with `new_frame`, updating with the real time it took
+    fn end_frame(app: &mut App) {
+        app.input_manager.process_event(&Event::MainEventsCleared);
+        app.update();
+    }
+
+    fn hold(app: &mut App, keys: &[VirtualKeyCode]) {
+        for &keycode in keys {
+            app.input_manager
+                .process_event(&key(keycode, ElementState::Pressed));
+        }
+    }
+
+    #[test]
+    fn movement_input_is_at_most_unit_length() {
+        use VirtualKeyCode::{A, D, S, W};
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        // the player starts facing along negative x
+        let cases: &[(&[VirtualKeyCode], (f32, f32))] = &[
+            (&[], (0.0, 0.0)),
+            (&[W], (-1.0, 0.0)),
+            (&[S], (1.0, 0.0)),
+            (&[D], (0.0, -1.0)),
+            (&[A], (0.0, 1.0)),
+            (&[W, D], (-diagonal, -diagonal)),
+            (&[W, A], (-diagonal, diagonal)),
+            (&[W, S], (0.0, 0.0)),
+            (&[A, D], (0.0, 0.0)),
+        ];
+        for (keys, (expected_x, expected_y)) in cases {
+            let mut app = test_app();
+            hold(&mut app, keys);
+            let (move_x, move_y) = app.movement_input();
+            assert!(
+                (move_x.powi(2) + move_y.powi(2)).sqrt() <= 1.0 + 1e-6,
+                "{keys:?} moved too far"
+            );
+            assert!(
+                (move_x - expected_x).abs() < 1e-6 && (move_y - expected_y).abs() < 1e-6,
+                "{keys:?} moved along ({move_x}, {move_y})"
+            );
+        }
+    }
+
+    #[test]
+    fn no_input_leaves_player_in_place() {
+        let mut app = test_app();
+        let start = (app.player_x, app.player_y);
+        app.advance(1.0 / 60.0);
+        assert_eq!((app.player_x, app.player_y), start);
+    }
+
+    #[test]
+    fn projectile_destroys_barrel() {
+        let mut app = test_app();
+        app.entities = vec![Entity::new(8.5, 1.5, 3, EntityType::Stationary)];
+        place(&mut app, 6.5, 1.5, 1.0, 0.0);
+
+        shoot(&mut app);
+        assert_eq!(app.entities.len(), 2);
+        for _ in 0..60 {
+            app.advance(1.0 / 60.0);
+        }
+        // both the projectile and the barrel it hit are gone
+        assert!(app.entities.is_empty());
+    }
+
+    #[test]
+    fn new_map_starts_with_default_lighting() {
+        let mut app = test_app();
+        app.ambient = 0.3;
+        app.side_shade = [0.2, 0.4];
+        app.lights.push(Light {
+            x_pos: 1.5,
+            y_pos: 1.5,
+            radius: 2.0,
+            color: [1.0; 3],
+        });
+        let open = || Map::new(vec![vec![0; 3]; 3]);
+        app.set_map("open", open(), open(), open());
+        assert_eq!(app.ambient, DEFAULT_AMBIENT);
+        assert_eq!(app.side_shade, DEFAULT_SIDE_SHADE);
+        assert!(app.lights.is_empty());
+    }
+
+    #[test]
+    fn projectile_leaving_map_is_removed() {
+        let mut app = test_app();
+        // nothing to hit on the way out
+        let open = || Map::new(vec![vec![0; 3]; 3]);
+        app.set_map("open", open(), open(), open());
+        app.entities.push(Entity::new(
+            2.9,
+            1.5,
+            5,
+            EntityType::Projectile(8.0, 0.0, Owner::Player),
+        ));
+
+        app.advance(1.0 / 60.0);
+        assert!(app.entities.is_empty());
+    }
+
+    #[test]
+    fn walking_onto_health_pickup_heals() {
+        let mut app = test_app();
+        app.entities = vec![Entity::new(
+            2.5,
+            1.5,
+            3,
+            EntityType::Pickup {
+                kind: PickupKind::Health,
+            },
+        )];
+        app.health = 50;
+        place(&mut app, 2.5, 3.5, 0.0, -1.0);
+
+        hold(&mut app, &[VirtualKeyCode::W]);
+        for _ in 0..30 {
+            app.advance(1.0 / 60.0);
+        }
+        assert_eq!(app.health, 50 + HEALTH_PICKUP_AMOUNT);
+        assert!(app.entities.is_empty());
+    }
+
+    #[test]
+    fn shooting_without_ammo_fires_nothing() {
+        let mut app = test_app();
+        app.entities.clear();
+        app.ammo = 0;
+
+        shoot(&mut app);
+        assert!(app.entities.is_empty());
+        assert_eq!(app.ammo, 0);
+    }
+
+    #[test]
+    fn automatic_weapon_fires_at_fire_rate() {
+        let mut app = test_app();
+        app.automatic = true;
+        app.fire_rate = 10.0;
+        let ammo = app.ammo;
+
+        app.input_manager.process_event(&new_frame());
+        app.input_manager
+            .process_event(&mouse(MouseButton::Left, ElementState::Pressed));
+        for _ in 0..60 {
+            app.advance(1.0 / 60.0);
+            app.input_manager.process_event(&new_frame());
+        }
+        // the cooldown only runs out on whole frames, so a shot can slip a frame late
+        let fired = ammo - app.ammo;
+        assert!((9..=11).contains(&fired), "fired {fired} shots");
+    }
+
+    #[test]
+    fn new_map_starts_with_starting_ammo() {
+        let mut app = test_app();
+        app.starting_ammo = 5;
+        shoot(&mut app);
+        let open = || Map::new(vec![vec![0; 3]; 3]);
+        app.set_map("open", open(), open(), open());
+        assert_eq!(app.ammo, 5);
+    }
+
+    #[test]
+    fn infinite_ammo_fires_without_using_ammo() {
+        let mut app = test_app();
+        app.entities.clear();
+        app.ammo = 0;
+        app.infinite_ammo = true;
+
+        shoot(&mut app);
+        assert_eq!(app.entities.len(), 1);
+        assert_eq!(app.ammo, 0);
+    }
+
+    #[test]
+    fn rotations_pick_the_side_being_seen() {
+        let entity = Entity::new(2.0, 2.0, 0, EntityType::Stationary)
+            .with_rotations([10, 11, 12, 13, 14, 15, 16, 17])
+            .with_facing(std::f32::consts::FRAC_PI_2);
+        // facing positive y, so a viewer there sees its front
+        assert_eq!(entity.current_texture(2.0, 5.0), 10);
+        assert_eq!(entity.current_texture(2.0, -1.0), 14);
+        // a quarter turn counterclockwise from its front
+        assert_eq!(entity.current_texture(-1.0, 2.0), 12);
+        assert_eq!(entity.current_texture(5.0, 5.0), 17);
+    }
+
+    #[test]
+    fn spawn_in_wall_moves_next_door() {
+        let walls = Map::new(vec![vec![1, 1, 1], vec![1, 1, 0], vec![1, 1, 1]]);
+        assert_eq!(find_valid_spawn(&walls, (1.5, 1.5)), (2.5, 1.5));
+        // open spawns are left exactly where they are
+        assert_eq!(find_valid_spawn(&walls, (2.25, 1.75)), (2.25, 1.75));
+    }
+
+    #[test]
+    fn keyboard_turn_is_frame_rate_independent() {
+        let turn_speed = 6.0;
+        let quarter_turn = std::f32::consts::FRAC_PI_2 / turn_speed;
+        for frames in [1, 7, 60] {
+            let mut app = test_app();
+            app.keyboard_turn_speed = turn_speed;
+            hold(&mut app, &[VirtualKeyCode::Right]);
+            for _ in 0..frames {
+                app.advance(quarter_turn / frames as f32);
+            }
+            // starting along negative x, a quarter turn right faces negative y
+            assert!(
+                app.dir_x.abs() < 1e-4 && (app.dir_y + 1.0).abs() < 1e-4,
+                "{frames} frames turned to ({}, {})",
+                app.dir_x,
+                app.dir_y
+            );
+        }
+    }
+
+    #[test]
+    fn headless_frame_shows_wall_ahead() {
+        let mut app = test_app();
+        // every texture a different flat color so pixels say what was drawn there
+        for id in 0..6u8 {
+            let color = image::Rgba([id * 40, 0xff - id * 40, 0x80, 0xff]);
+            app.textures
+                .push(DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+                    4, 4, color,
+                )));
+        }
+        // close enough to the top wall that it fills the view at full brightness
+        place(&mut app, 1.5, 1.5, 0.0, -1.0);
+
+        app.draw();
+        let frame = app.renderer.capture();
+        let center = frame.get_pixel(WIDTH as u32 / 2, HEIGHT as u32 / 2);
+        assert_eq!(center.0, [0, 0xff, 0x80, 0xff]);
+    }
+
+    #[test]
+    fn light_brightens_wall_and_floor_alike() {
+        let mut app = test_app();
+        for _ in 0..6 {
+            app.textures
+                .push(DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+                    4,
+                    4,
+                    image::Rgba([0x60, 0x60, 0x60, 0xff]),
+                )));
+        }
+        let walls = (0..5)
+            .map(|y| {
+                (0..5)
+                    .map(|x| u32::from(x % 4 == 0 || y % 4 == 0))
+                    .collect()
+            })
+            .collect();
+        let open = || Map::new(vec![vec![1; 5]; 5]);
+        app.set_map("room", Map::new(walls), open(), open());
+        app.side_shade = [1.0, 1.0];
+        app.fog_density = 0.0;
+        // right where the wall ahead meets the floor
+        app.lights.push(Light {
+            x_pos: 2.5,
+            y_pos: 1.2,
+            radius: 1.5,
+            color: [1.0; 3],
+        });
+        place(&mut app, 2.5, 3.5, 0.0, -1.0);
+
+        app.draw();
+        let x = WIDTH / 2;
+        let wall_bottom = app.frame.floor.wall_rows[x as usize].end;
+        let frame = app.renderer.capture();
+        let wall = frame.get_pixel(x as u32, wall_bottom as u32 - 1);
+        let floor = frame.get_pixel(x as u32, wall_bottom as u32);
+        for (wall, floor) in wall.0.iter().zip(floor.0).take(3) {
+            assert!(
+                wall.abs_diff(floor) <= 8,
+                "wall {wall} against floor {floor}"
+            );
+        }
+    }
+
+    #[test]
+    fn drawing_without_textures_falls_back() {
+        let mut app = test_app();
+        assert_eq!(app.missing_texture_id(), None);
+        app.draw();
+        let frame = app.renderer.capture();
+        assert!(frame.pixels().any(|pixel| pixel.0 == [0xff, 0, 0xff, 0xff]));
+
+        let id = app
+            .textures
+            .push_missing_texture(textures::missing_texture());
+        assert_eq!(app.missing_texture_id(), Some(id));
+    }
+
+    #[test]
+    fn frames_share_the_maps() {
+        let mut app = test_app();
+        app.show_minimap = true;
+        app.draw();
+        assert!(Arc::ptr_eq(&app.frame.floor.floor, &app.floor));
+        assert!(Arc::ptr_eq(&app.frame.floor.ceiling, &app.ceiling));
+        assert!(Arc::ptr_eq(&app.frame.minimap_walls, &app.walls));
+
+        // changing a tile leaves the drawn frame's copy alone
+        Arc::make_mut(&mut app.walls).set_tile(1, 1, 1);
+        assert!(!app.frame.minimap_walls.is_solid(1, 1));
+        app.draw();
+        assert!(app.frame.minimap_walls.is_solid(1, 1));
+    }
+
+    #[test]
+    fn wall_up_close_keeps_its_texture() {
+        let mut app = test_app();
+        app.textures
+            .push_missing_texture(textures::missing_texture());
+        // well inside the collision box, as if noclipping into the wall
+        place(&mut app, 1.5, 1.001, 0.0, -1.0);
+
+        app.draw();
+        let frame = app.renderer.capture();
+        let first = frame.get_pixel(0, 0);
+        assert!(frame.pixels().any(|pixel| pixel != first));
+    }
+
+    #[test]
+    fn large_map_draws_and_collides() {
+        const SIZE: usize = 100;
+        let mut app = test_app();
+        app.textures
+            .push_missing_texture(textures::missing_texture());
+        // walled in around the edge with a single pillar near the middle
+        let mut walls: Vec<Vec<u32>> = (0..SIZE)
+            .map(|y| {
+                (0..SIZE)
+                    .map(|x| u32::from(x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1))
+                    .collect()
+            })
+            .collect();
+        walls[50][53] = 1;
+        let open = || Map::new(vec![vec![1; SIZE]; SIZE]);
+        app.set_map("large", Map::new(walls), open(), open());
+        app.show_minimap = true;
+        place(&mut app, 50.5, 50.5, 1.0, 0.0);
+
+        app.draw();
+        let center = app.depth_at_column(app.renderer.width() / 2);
+        assert!((center - 2.5).abs() < 0.01, "pillar drawn {center} away");
+
+        // walking into the pillar stops short of it
+        hold(&mut app, &[VirtualKeyCode::W]);
+        for _ in 0..120 {
+            app.advance(1.0 / 60.0);
+        }
+        assert!(app.player_x > 52.0 && app.player_x <= 53.0 - PLAYER_RADIUS);
+        assert_eq!(app.player_y, 50.5);
+    }
+
+    #[test]
+    fn internal_resolution_keeps_pixels_square() {
+        let mut app = test_app();
+        assert!(app.set_internal_resolution(0, 180).is_err());
+        assert!(app.set_internal_resolution(320, -1).is_err());
+        assert_eq!(app.renderer.width(), WIDTH);
+
+        // 4:3 is narrower than the default 16:9 so less is seen to the sides
+        app.set_internal_resolution(320, 240).unwrap();
+        assert_eq!(app.renderer.capture().dimensions(), (320, 240));
+        let plane_len = (app.plane_x.powi(2) + app.plane_y.powi(2)).sqrt();
+        assert!((plane_len - 320.0 / 240.0 / 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fps_is_shown_in_title() {
+        let mut app = test_app();
+        hold(&mut app, &[VirtualKeyCode::F3]);
+        app.advance(FPS_TITLE_INTERVAL);
+        assert_eq!(
+            app.pending_title.as_deref(),
+            Some("Wolfenstein Clone - 0.0 fps")
+        );
+
+        // turning it back off puts the plain title back
+        app.input_manager.process_event(&new_frame());
+        hold(&mut app, &[VirtualKeyCode::F3]);
+        app.advance(0.0);
+        assert_eq!(app.pending_title.as_deref(), Some(TITLE));
+    }
+
+    #[test]
+    fn debug_toggle_leaves_debug_modes() {
+        let mut app = test_app();
+        app.input_manager.process_event(&new_frame());
+        hold(&mut app, &[VirtualKeyCode::F1]);
+        end_frame(&mut app);
+        assert!(app.debug);
+
+        app.noclip = true;
+        app.single_step = true;
+        app.input_manager.process_event(&new_frame());
+        hold(&mut app, &[VirtualKeyCode::F1]);
+        end_frame(&mut app);
+        assert!(!app.debug && !app.noclip && !app.single_step);
+    }
+
+    #[test]
+    fn enemy_shoots_player_in_sight() {
+        let mut app = test_app();
+        app.entities = vec![Entity::new(
+            7.5,
+            1.5,
+            3,
+            EntityType::Enemy {
+                speed: 1.0,
+                health: 30,
+            },
+        )];
+        place(&mut app, 2.5, 1.5, 1.0, 0.0);
+        app.health = PROJECTILE_DAMAGE;
+
+        for _ in 0..60 * 5 {
+            app.advance(1.0 / 60.0);
+            if app.is_dead() {
+                break;
+            }
+        }
+        assert!(app.is_dead());
+        assert!(app.screen_flash.is_some());
+    }
+
+    /// presses use for a single frame then lets the wall slide as far as it can
+    fn use_and_wait(app: &mut App) {
+        app.input_manager.process_event(&new_frame());
+        hold(app, &[VirtualKeyCode::E]);
+        app.advance(0.0);
+        app.input_manager
+            .process_event(&key(VirtualKeyCode::E, ElementState::Released));
+        app.input_manager.process_event(&new_frame());
+        app.advance(2.0 / PUSH_WALL_SPEED);
+    }
+
+    #[test]
+    fn push_wall_only_slides_once() {
+        let mut app = test_app();
+        app.entities.clear();
+        place(&mut app, 4.5, 4.5, 0.0, 1.0);
+        use_and_wait(&mut app);
+        assert!(!app.walls.is_solid(4, 5));
+        assert!(app.walls.is_solid(4, 6));
+
+        // walking up to it again and pushing does nothing
+        place(&mut app, 4.5, 5.5, 0.0, 1.0);
+        use_and_wait(&mut app);
+        assert!(app.walls.is_solid(4, 6));
+        assert!(!app.walls.is_solid(4, 7));
+    }
+
+    #[test]
+    fn push_wall_wont_trap_entities() {
+        let mut app = test_app();
+        app.entities = vec![Entity::new(
+            4.5,
+            6.5,
+            3,
+            EntityType::Enemy {
+                speed: 0.0,
+                health: 30,
+            },
+        )];
+        place(&mut app, 4.5, 4.5, 0.0, 1.0);
+        use_and_wait(&mut app);
+        assert!(app.walls.is_solid(4, 5));
+        assert!(!app.walls.is_solid(4, 6));
+    }
+
+    #[test]
+    fn gradient_sky_replaces_ceiling() {
+        let mut app = test_app();
+        app.textures
+            .push_missing_texture(textures::missing_texture());
+        let zenith = [0x10, 0x20, 0x80, 0xff];
+        app.sky_mode = SkyMode::Gradient {
+            zenith,
+            horizon: [0xff; 4],
+        };
+        // looking down the long side of the room so the top of the screen is open
+        place(&mut app, 1.5, 8.5, 1.0, 0.0);
+
+        app.draw();
+        assert_eq!(
+            app.renderer.capture().get_pixel(WIDTH as u32 / 2, 0).0,
+            zenith
+        );
+    }
+}
@@ -0,0 +1,99 @@
+/// set on a floor or ceiling tile to mirror its texture across the tile's x axis
+pub const FLIP_X: u32 = 1 << 31;
+/// set on a floor or ceiling tile to mirror its texture across the tile's y axis
+pub const FLIP_Y: u32 = 1 << 30;
+/// set on a floor or ceiling tile to turn its texture a quarter turn, done before any flip
+pub const ROTATE_90: u32 = 1 << 29;
+const TILE_FLAGS: u32 = FLIP_X | FLIP_Y | ROTATE_90;
+
+/// splits a tile value into its id and orientation flags
+pub fn split_tile(tile: u32) -> (u32, u32) {
+    (tile & !TILE_FLAGS, tile & TILE_FLAGS)
+}
+
+/// moves a position within a tile, both in 0..1, to where the
+/// tile's texture should be sampled given its orientation flags
+pub fn orient(flags: u32, x: f32, y: f32) -> (f32, f32) {
+    let (mut x, mut y) = if flags & ROTATE_90 != 0 {
+        (y, 1.0 - x)
+    } else {
+        (x, y)
+    };
+    if flags & FLIP_X != 0 {
+        x = 1.0 - x;
+    }
+    if flags & FLIP_Y != 0 {
+        y = 1.0 - y;
+    }
+    (x, y)
+}
+
+/// grid of tile values indexed by whole tile coordinates, 0 is an empty tile
+#[derive(Default)]
+pub struct Map {
+    tiles: Vec<Vec<u32>>,
+}
+
+// written out so `clone_from` reuses the rows already allocated
+impl Clone for Map {
+    fn clone(&self) -> Self {
+        Self {
+            tiles: self.tiles.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.tiles.clone_from(&source.tiles);
+    }
+}
+
+impl Map {
+    pub fn new(tiles: Vec<Vec<u32>>) -> Self {
+        Self { tiles }
+    }
+
+    /// number of tiles along the x axis, taken from the first row
+    pub fn width(&self) -> usize {
+        self.tiles.first().map_or(0, Vec::len)
+    }
+
+    /// number of tiles along the y axis
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// returns whether the tile coordinates are inside the map
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && self
+                .tiles
+                .get(y as usize)
+                .is_some_and(|row| (x as usize) < row.len())
+    }
+
+    /// value of the tile at the given coordinates, 0 for anything outside the map
+    pub fn tile_at(&self, x: i32, y: i32) -> u32 {
+        if !self.contains(x, y) {
+            return 0;
+        }
+        self.tiles[y as usize][x as usize]
+    }
+
+    /// returns whether the tile blocks movement and sight, everything outside the map is solid
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        !self.contains(x, y) || self.tile_at(x, y) != 0
+    }
+
+    /// replaces the tile at the given coordinates, doing nothing outside the map
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: u32) {
+        if self.contains(x, y) {
+            self.tiles[y as usize][x as usize] = tile;
+        }
+    }
+
+    /// every row of tiles, top to bottom
+    pub fn rows(&self) -> &[Vec<u32>] {
+        &self.tiles
+    }
+}
@@ -1,44 +1,358 @@
-#![allow(dead_code)]
-
-use anyhow::Result;
-use image::{math::Rect, DynamicImage, GenericImageView};
+use anyhow::{bail, Context, Result};
+use image::{math::Rect, DynamicImage, GenericImageView, Rgba, RgbaImage};
 use pixels::{Pixels, SurfaceTexture};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::slice::{ChunksExactMut, ParallelSliceMut};
 use winit::{dpi::PhysicalSize, window::Window};
 
+use crate::map::Map;
+use crate::scaler::{Scaler, ScalingMode};
+
+/// distance from the center of the screen where a vignette starts, as a
+/// fraction of the distance to the edge
+const VIGNETTE_INNER_RADIUS: f32 = 0.4;
+
+/// how texels are picked when sampling a texture
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingMode {
+    /// uses the single closest texel
+    #[default]
+    Nearest,
+    /// interpolates between the four closest texels
+    Bilinear,
+}
+
+/// view of the world used to project sprites onto the screen
+#[derive(Default)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub dir_x: f32,
+    pub dir_y: f32,
+    pub plane_x: f32,
+    pub plane_y: f32,
+    /// screen row that the camera looks straight ahead at
+    pub horizon: i32,
+    /// distance to the closest wall for each screen column
+    pub depth: Vec<f32>,
+    /// depth below which sprites are too close to the camera to be drawn
+    pub near_clip: f32,
+    pub fog_color: [u8; 4],
+    /// how quickly things fade into the fog with distance, 0.0 disables fog
+    pub fog_density: f32,
+}
+
+impl Camera {
+    /// projects a world position onto the screen, returning the screen column
+    /// it is centered on (before mirroring) and its depth from the camera.
+    /// returns `None` for positions behind the camera or closer than the near clip
+    pub fn project(&self, world_x: f32, world_y: f32, screen_width: i32) -> Option<(i32, f32)> {
+        let rel_x = world_x - self.x;
+        let rel_y = world_y - self.y;
+
+        let inv_det = 1.0 / (self.plane_x * self.dir_y - self.dir_x * self.plane_y);
+        let transform_x = inv_det * (self.dir_y * rel_x - self.dir_x * rel_y);
+        let transform_y = inv_det * (-self.plane_y * rel_x + self.plane_x * rel_y);
+        if transform_y <= self.near_clip.max(0.0) {
+            return None;
+        }
+
+        let screen_x = ((screen_width / 2) as f32 * (1.0 + transform_x / transform_y)) as i32;
+        Some((screen_x, transform_y))
+    }
+
+    /// returns whether something at the given depth is hidden by a wall in the given column
+    pub fn is_occluded(&self, screen_x: i32, depth: f32) -> bool {
+        usize::try_from(screen_x)
+            .ok()
+            .and_then(|x| self.depth.get(x))
+            .is_some_and(|wall| *wall < depth)
+    }
+
+    /// fog color with its alpha set to how much fog covers the given distance
+    pub fn fog_overlay(&self, dist: f32) -> [u8; 4] {
+        let amount = fog_amount(dist, self.fog_density);
+        [
+            self.fog_color[0],
+            self.fog_color[1],
+            self.fog_color[2],
+            (amount * 255.0) as u8,
+        ]
+    }
+}
+
+/// storage that the renderer draws frames into
+pub trait FrameBuffer {
+    fn frame(&self) -> &[u8];
+    fn frame_mut(&mut self) -> &mut [u8];
+    /// shows the frame on screen, if there is one
+    fn present(&self) -> Result<()>;
+    /// changes the size of the frame, the contents are lost
+    fn resize_buffer(&mut self, width: u32, height: u32) -> Result<()>;
+    /// changes the size of the surface the frame is shown on
+    fn resize_surface(&mut self, width: u32, height: u32) -> Result<()>;
+    /// changes how the frame is fit onto the surface, if there is one
+    fn set_scaling_mode(&mut self, _mode: ScalingMode) {}
+}
+
+/// frame buffer shown in a window through `pixels`
+pub struct WindowBuffer {
+    pixels: Pixels,
+    scaler: Scaler,
+    surface_size: (u32, u32),
+    scaling_mode: ScalingMode,
+}
+
+impl WindowBuffer {
+    pub fn new(window: &Window, width: u32, height: u32) -> Result<Self> {
+        let window_size = window.inner_size();
+        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, window);
+        let pixels = Pixels::new(width, height, surface_texture)?;
+        let surface_size = (window_size.width, window_size.height);
+        let scaling_mode = ScalingMode::default();
+        Ok(Self {
+            scaler: Scaler::new(&pixels, surface_size, scaling_mode),
+            pixels,
+            surface_size,
+            scaling_mode,
+        })
+    }
+}
+
+impl FrameBuffer for WindowBuffer {
+    fn frame(&self) -> &[u8] {
+        self.pixels.frame()
+    }
+
+    fn frame_mut(&mut self) -> &mut [u8] {
+        self.pixels.frame_mut()
+    }
+
+    fn present(&self) -> Result<()> {
+        Ok(self.pixels.render_with(|encoder, render_target, _| {
+            self.scaler.render(encoder, render_target);
+            Ok(())
+        })?)
+    }
+
+    fn resize_buffer(&mut self, width: u32, height: u32) -> Result<()> {
+        self.pixels.resize_buffer(width, height)?;
+        // the frame texture is recreated so the scaler has to bind the new one
+        self.scaler = Scaler::new(&self.pixels, self.surface_size, self.scaling_mode);
+        Ok(())
+    }
+
+    fn resize_surface(&mut self, width: u32, height: u32) -> Result<()> {
+        self.pixels.resize_surface(width, height)?;
+        self.surface_size = (width, height);
+        self.scaler
+            .resize(&self.pixels, self.surface_size, self.scaling_mode);
+        Ok(())
+    }
+
+    fn set_scaling_mode(&mut self, mode: ScalingMode) {
+        self.scaling_mode = mode;
+        self.scaler
+            .resize(&self.pixels, self.surface_size, self.scaling_mode);
+    }
+}
+
+/// frame buffer that only lives in memory, used to draw without a window
+pub struct HeadlessBuffer {
+    pixels: Vec<u8>,
+}
+
+impl HeadlessBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+}
+
+impl FrameBuffer for HeadlessBuffer {
+    fn frame(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    fn frame_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    fn present(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn resize_buffer(&mut self, width: u32, height: u32) -> Result<()> {
+        self.pixels = vec![0; (width * height * 4) as usize];
+        Ok(())
+    }
+
+    fn resize_surface(&mut self, _width: u32, _height: u32) -> Result<()> {
+        Ok(())
+    }
+}
+
 pub struct Renderer {
     width: i32,
     height: i32,
-    pub frame_buffer: Pixels,
+    pub frame_buffer: Box<dyn FrameBuffer>,
+    pub filtering: SamplingMode,
+    scaling_mode: ScalingMode,
 }
 
 impl Renderer {
     pub fn new(window: &Window, width: i32, height: i32) -> Result<Self> {
-        let window_size = window.inner_size();
-        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
         Ok(Self {
             width,
             height,
-            frame_buffer: Pixels::new(width as u32, height as u32, surface_texture)?,
+            frame_buffer: Box::new(WindowBuffer::new(window, width as u32, height as u32)?),
+            filtering: SamplingMode::default(),
+            scaling_mode: ScalingMode::default(),
         })
     }
 
+    /// creates a renderer that draws into memory instead of a window
+    pub fn headless(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            frame_buffer: Box::new(HeadlessBuffer::new(width as u32, height as u32)),
+            filtering: SamplingMode::default(),
+            scaling_mode: ScalingMode::default(),
+        }
+    }
+
+    /// width of the internal pixel buffer
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// height of the internal pixel buffer
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// changes the resolution the frame is drawn at, independent of the window size.
+    /// the contents of the frame are lost
+    pub fn set_internal_resolution(&mut self, width: i32, height: i32) -> Result<()> {
+        if width <= 0 || height <= 0 {
+            bail!("invalid internal resolution {width}x{height}");
+        }
+        self.frame_buffer
+            .resize_buffer(width as u32, height as u32)?;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// copies the internal pixel buffer into an image, at the internal
+    /// resolution rather than the upscaled window size
+    pub fn capture(&self) -> RgbaImage {
+        RgbaImage::from_raw(
+            self.width as u32,
+            self.height as u32,
+            self.frame_buffer.frame().to_vec(),
+        )
+        .expect("frame buffer size matches the internal resolution")
+    }
+
     /// renders the pixel buffer to the screen texture
     pub fn render(&self) -> Result<()> {
-        Ok(self.frame_buffer.render()?)
+        self.frame_buffer.present()
     }
 
-    /// resizes the pixel buffer to the nearest integer scale
-    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+    /// how the frame is fit into the window
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.scaling_mode
+    }
+
+    /// changes how the frame is fit into the window, taking effect immediately
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) {
+        self.scaling_mode = mode;
+        self.frame_buffer.set_scaling_mode(mode);
+    }
+
+    /// resizes the surface the frame is drawn to, fitting the frame
+    /// into it according to the scaling mode
+    pub fn resize(&mut self, size: PhysicalSize<u32>) -> Result<()> {
+        // minimized windows report a zero size, which the surface can't be resized to
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
         self.frame_buffer
             .resize_surface(size.width, size.height)
-            .expect("failed to resize surface");
+            .with_context(|| format!("failed to resize surface to {}x{}", size.width, size.height))
     }
 
     /// fills the frame with the given color
     pub fn fill(&mut self, color: &[u8; 4]) {
-        self.frame_buffer
-            .frame_mut()
-            .copy_from_slice(&color.repeat((self.width * self.height) as usize));
+        for pixel in self.frame_buffer.frame_mut().chunks_exact_mut(4) {
+            pixel.copy_from_slice(color);
+        }
+    }
+
+    /// blends the given color over the whole frame based on the color's alpha
+    pub fn fill_blended(&mut self, color: &[u8; 4]) {
+        let alpha = color[3] as f32 / 255.0;
+        for pixel in self.frame_buffer.frame_mut().chunks_exact_mut(4) {
+            for i in 0..3 {
+                pixel[i] = (color[i] as f32 * alpha + pixel[i] as f32 * (1.0 - alpha)) as u8;
+            }
+        }
+    }
+
+    /// blends the given color over the edges of the frame, fading out toward the center.
+    /// the color's alpha is how strongly it covers the very edges
+    pub fn draw_vignette(&mut self, color: &[u8; 4]) {
+        let alpha = color[3] as f32 / 255.0;
+        let half_width = self.width as f32 / 2.0;
+        let half_height = self.height as f32 / 2.0;
+        self.rows_mut().enumerate().for_each(|(y, row)| {
+            let dy = (y as f32 + 0.5 - half_height) / half_height;
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let dx = (x as f32 + 0.5 - half_width) / half_width;
+                // nothing inside the inner part of the screen, full strength at the edges
+                let edge = ((dx * dx + dy * dy).sqrt().min(1.0) - VIGNETTE_INNER_RADIUS)
+                    / (1.0 - VIGNETTE_INNER_RADIUS);
+                if edge <= 0.0 {
+                    continue;
+                }
+                let amount = alpha * edge * edge;
+                for i in 0..3 {
+                    pixel[i] = (color[i] as f32 * amount + pixel[i] as f32 * (1.0 - amount)) as u8;
+                }
+            }
+        });
+    }
+
+    /// fills the rows from `y0` up to but not including `y1` with colors
+    /// blending from `top_color` on the first row to `bottom_color` on the last
+    pub fn draw_vertical_gradient(
+        &mut self,
+        top_color: &[u8; 4],
+        bottom_color: &[u8; 4],
+        y0: i32,
+        y1: i32,
+    ) {
+        let rows = y1 - y0;
+        let row_len = (self.width * 4) as usize;
+        let frame = self.frame_buffer.frame_mut();
+        for y in y0.max(0)..y1.min(self.height) {
+            let t = if rows > 1 {
+                (y - y0) as f32 / (rows - 1) as f32
+            } else {
+                0.0
+            };
+            let mut color = [0; 4];
+            for i in 0..4 {
+                color[i] = (top_color[i] as f32 * (1.0 - t) + bottom_color[i] as f32 * t) as u8;
+            }
+            let offset = y as usize * row_len;
+            for pixel in frame[offset..offset + row_len].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
     }
 
     /// draws a vertical line starting at the given x and top y with the given height
@@ -47,19 +361,17 @@ impl Renderer {
             return -1;
         }
 
-        let actual_height = if top_y + height > self.height {
-            self.height - top_y
-        } else if top_y < 0 {
-            (top_y + height).max(0)
-        } else {
-            height
-        };
+        // clip both ends so lines crossing the whole frame don't wrap around
+        let actual_height = (top_y + height).min(self.height) - top_y.max(0);
 
         for pixel in self
             .frame_buffer
             .frame_mut()
             .chunks_exact_mut(4)
-            .skip((x.clamp(0, self.width - 1) + self.width * top_y.clamp(0, self.height)) as usize)
+            .skip(
+                (x.clamp(0, self.width - 1) + self.width * top_y.clamp(0, self.height - 1))
+                    as usize,
+            )
             .step_by(self.width as usize)
             .take(actual_height as usize)
         {
@@ -75,19 +387,17 @@ impl Renderer {
             return -1;
         }
 
-        let actual_width = if left_x + width > self.width {
-            self.width - left_x
-        } else if left_x < 0 {
-            (left_x + width).max(0)
-        } else {
-            width
-        };
+        // clip both ends so lines crossing the whole frame don't wrap around
+        let actual_width = (left_x + width).min(self.width) - left_x.max(0);
 
         for pixel in self
             .frame_buffer
             .frame_mut()
             .chunks_exact_mut(4)
-            .skip((left_x.clamp(0, self.width - 1) + self.width * y.clamp(0, self.height)) as usize)
+            .skip(
+                (left_x.clamp(0, self.width - 1) + self.width * y.clamp(0, self.height - 1))
+                    as usize,
+            )
             .take(actual_width as usize)
         {
             pixel.copy_from_slice(color);
@@ -139,7 +449,7 @@ impl Renderer {
         for y in y1..=y2 {
             if x >= 0 && x < self.width && y >= 0 && y < self.height {
                 let offset = ((y * self.width + x) * 4) as usize;
-                frame[offset + 0] = color[0];
+                frame[offset] = color[0];
                 frame[offset + 1] = color[1];
                 frame[offset + 2] = color[2];
                 frame[offset + 3] = color[3];
@@ -172,7 +482,7 @@ impl Renderer {
         for x in x1..=x2 {
             if x >= 0 && x < self.width && y >= 0 && y < self.height {
                 let offset = ((y * self.width + x) * 4) as usize;
-                frame[offset + 0] = color[0];
+                frame[offset] = color[0];
                 frame[offset + 1] = color[1];
                 frame[offset + 2] = color[2];
                 frame[offset + 3] = color[3];
@@ -187,6 +497,115 @@ impl Renderer {
         }
     }
 
+    /// fills the row between the given left and right x, both included
+    fn draw_span(&mut self, color: &[u8; 4], left: i32, right: i32, y: i32) {
+        // clipped to the frame before measuring so far off screen spans can't overflow
+        let left = left.max(0);
+        let right = right.min(self.width - 1);
+        if right >= left {
+            self.draw_hori_line(color, left, y, right - left + 1);
+        }
+    }
+
+    /// draws a circle around the given center using the midpoint circle algorithm
+    pub fn draw_circle(
+        &mut self,
+        color: &[u8; 4],
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        filled: bool,
+    ) {
+        if radius < 0 {
+            return;
+        }
+
+        let mut x = radius;
+        let mut y = 0;
+        let mut d = 1 - radius;
+
+        while x >= y {
+            if filled {
+                // each step fills the spans of the four octant pairs
+                self.draw_span(color, center_x - x, center_x + x, center_y + y);
+                self.draw_span(color, center_x - x, center_x + x, center_y - y);
+                self.draw_span(color, center_x - y, center_x + y, center_y + x);
+                self.draw_span(color, center_x - y, center_x + y, center_y - x);
+            } else {
+                self.draw_pixel(color, center_x + x, center_y + y);
+                self.draw_pixel(color, center_x - x, center_y + y);
+                self.draw_pixel(color, center_x + x, center_y - y);
+                self.draw_pixel(color, center_x - x, center_y - y);
+                self.draw_pixel(color, center_x + y, center_y + x);
+                self.draw_pixel(color, center_x - y, center_y + x);
+                self.draw_pixel(color, center_x + y, center_y - x);
+                self.draw_pixel(color, center_x - y, center_y - x);
+            }
+
+            y += 1;
+            if d < 0 {
+                d += 2 * y + 1;
+            } else {
+                x -= 1;
+                d += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// draws a triangle between the three given x,y points
+    pub fn draw_triangle(
+        &mut self,
+        color: &[u8; 4],
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        filled: bool,
+    ) {
+        if !filled {
+            self.draw_line(color, p0.0, p0.1, p1.0, p1.1);
+            self.draw_line(color, p1.0, p1.1, p2.0, p2.1);
+            self.draw_line(color, p2.0, p2.1, p0.0, p0.1);
+            return;
+        }
+
+        let mut points = [p0, p1, p2];
+        points.sort_by_key(|(_, y)| *y);
+        let [(x0, y0), (x1, y1), (x2, y2)] = points;
+
+        // x position along an edge at the given row
+        let edge_x = |(ax, ay): (i32, i32), (bx, by): (i32, i32), y: i32| {
+            if ay == by {
+                ax as f32
+            } else {
+                // in floats so points far apart can't overflow
+                let (ax, ay, bx, by) = (ax as f32, ay as f32, bx as f32, by as f32);
+                ax + (bx - ax) * (y as f32 - ay) / (by - ay)
+            }
+        };
+
+        // only the rows that are on screen need to be filled
+        for y in y0.max(0)..=y2.min(self.height - 1) {
+            let long_x = edge_x((x0, y0), (x2, y2), y);
+            let short_x = if y < y1 {
+                edge_x((x0, y0), (x1, y1), y)
+            } else {
+                edge_x((x1, y1), (x2, y2), y)
+            };
+
+            // collinear points can leave a flat triangle, so the row is
+            // always at least as wide as every point on it
+            let (mut left, mut right) = (long_x.min(short_x), long_x.max(short_x));
+            for (px, py) in points {
+                if py == y {
+                    left = left.min(px as f32);
+                    right = right.max(px as f32);
+                }
+            }
+
+            self.draw_span(color, left.round() as i32, right.round() as i32, y);
+        }
+    }
+
     /// draws a colored rectangle at the specified x,y coords with the given size
     pub fn draw_rectangle(&mut self, color: &[u8; 4], x: i32, y: i32, width: i32, height: i32) {
         for i in 0..width {
@@ -194,84 +613,319 @@ impl Renderer {
         }
     }
 
+    /// draws a crosshair of four lines around the given center, leaving a gap in the middle
+    pub fn draw_crosshair(
+        &mut self,
+        color: &[u8; 4],
+        center_x: i32,
+        center_y: i32,
+        size: i32,
+        gap: i32,
+    ) {
+        self.draw_hori_line(color, center_x - gap - size, center_y, size);
+        self.draw_hori_line(color, center_x + gap + 1, center_y, size);
+        self.draw_vert_line(color, center_x, center_y - gap - size, size);
+        self.draw_vert_line(color, center_x, center_y + gap + 1, size);
+    }
+
+    /// draws a top down view of the walls within `radius` tiles of the player, scrolling with
+    /// them, along with the player's position and facing direction
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_minimap(
+        &mut self,
+        walls: &Map,
+        player_x: f32,
+        player_y: f32,
+        dir_x: f32,
+        dir_y: f32,
+        origin_x: i32,
+        origin_y: i32,
+        cell_size: i32,
+        radius: i32,
+    ) {
+        // the tile in the top left corner of the minimap
+        let first_x = player_x.floor() as i32 - radius;
+        let first_y = player_y.floor() as i32 - radius;
+        for y in 0..=radius * 2 {
+            for x in 0..=radius * 2 {
+                let (tile_x, tile_y) = (first_x + x, first_y + y);
+                let color = if !walls.contains(tile_x, tile_y) {
+                    [0x00, 0x00, 0x00, 0xff]
+                } else if walls.tile_at(tile_x, tile_y) > 0 {
+                    [0xaa, 0xaa, 0xaa, 0xff]
+                } else {
+                    [0x22, 0x22, 0x22, 0xff]
+                };
+                self.draw_rectangle(
+                    &color,
+                    origin_x + x * cell_size,
+                    origin_y + y * cell_size,
+                    cell_size,
+                    cell_size,
+                );
+            }
+        }
+
+        let center_x = origin_x + ((player_x - first_x as f32) * cell_size as f32) as i32;
+        let center_y = origin_y + ((player_y - first_y as f32) * cell_size as f32) as i32;
+        let ray_length = (cell_size * 2) as f32;
+        let arrow_color = [0xff, 0xff, 0x00, 0xff];
+        self.draw_line(
+            &arrow_color,
+            center_x,
+            center_y,
+            center_x + (dir_x * ray_length) as i32,
+            center_y + (dir_y * ray_length) as i32,
+        );
+        // arrowhead at the end of the line, half as wide as it is long
+        let head_length = cell_size as f32;
+        let base_x = center_x as f32 + dir_x * (ray_length - head_length);
+        let base_y = center_y as f32 + dir_y * (ray_length - head_length);
+        let (side_x, side_y) = (-dir_y * head_length / 2.0, dir_x * head_length / 2.0);
+        self.draw_triangle(
+            &arrow_color,
+            (
+                center_x + (dir_x * ray_length) as i32,
+                center_y + (dir_y * ray_length) as i32,
+            ),
+            ((base_x + side_x) as i32, (base_y + side_y) as i32),
+            ((base_x - side_x) as i32, (base_y - side_y) as i32),
+            true,
+        );
+
+        self.draw_circle(
+            &[0xff, 0x00, 0x00, 0xff],
+            center_x,
+            center_y,
+            cell_size / 4,
+            true,
+        );
+    }
+
+    /// each row of rgba pixels in the frame from top to bottom, for writing
+    /// whole spans at once in parallel without checking every pixel's bounds
+    pub fn rows_mut(&mut self) -> ChunksExactMut<'_, u8> {
+        let row_len = (self.width * 4) as usize;
+        self.frame_buffer.frame_mut().par_chunks_exact_mut(row_len)
+    }
+
     /// draws a colored pixel at the given x,y coordinates
     pub fn draw_pixel(&mut self, color: &[u8; 4], x: i32, y: i32) {
         if x < 0 || x >= self.width || y < 0 || y >= self.height {
             return;
         }
 
-        let offset = ((y * self.width as i32 + x) * 4) as usize;
+        let offset = ((y * self.width + x) * 4) as usize;
         let frame = self.frame_buffer.frame_mut();
-        frame[offset + 0] = color[0];
+        frame[offset] = color[0];
         frame[offset + 1] = color[1];
         frame[offset + 2] = color[2];
         frame[offset + 3] = color[3];
     }
 
-    /// draws the given image at the specified x,y coords with the given size
-    pub fn draw_texture(
+    /// blends a colored pixel over the existing pixel at the given x,y coordinates
+    /// based on the color's alpha
+    pub fn blend_pixel(&mut self, color: &[u8; 4], x: i32, y: i32) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+
+        let offset = ((y * self.width + x) * 4) as usize;
+        let frame = self.frame_buffer.frame_mut();
+        let alpha = color[3] as f32 / 255.0;
+        for i in 0..3 {
+            frame[offset + i] =
+                (color[i] as f32 * alpha + frame[offset + i] as f32 * (1.0 - alpha)) as u8;
+        }
+        frame[offset + 3] = (color[3] as f32 + frame[offset + 3] as f32 * (1.0 - alpha)) as u8;
+    }
+
+    /// draws the image as a billboard standing at the given world position,
+    /// hiding any columns that are behind a wall. `scale` sizes it relative to a
+    /// full wall and `v_offset` pushes it down by a fraction of its own height
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sprite(
         &mut self,
         texture: &DynamicImage,
-        color: &[u8; 4],
-        x: i32,
-        y: i32,
-        size: PhysicalSize<u32>,
+        world_x: f32,
+        world_y: f32,
+        scale: f32,
+        v_offset: f32,
+        camera: &Camera,
     ) {
-        let clamped_y = y.clamp(0, self.height) as u32;
-        let clamped_x = x.clamp(0, self.width) as u32;
+        // dont draw sprites behind the camera
+        let Some((sprite_screen_x, transform_y)) = camera.project(world_x, world_y, self.width)
+        else {
+            return;
+        };
 
-        let x_scale = texture.width() as f32 / size.width as f32;
-        let y_scale = texture.height() as f32 / size.height as f32;
-        for c_y in ((clamped_y as i32 - y) as u32)..size.height {
-            let offset_y = c_y as i32 + y;
+        // size of a full wall at the sprite's distance
+        let full_height = (self.height as f32 / transform_y).abs();
+        let sprite_width = (full_height * scale) as i32;
+        let sprite_height = (full_height * scale) as i32;
+        if sprite_width <= 0 || sprite_height <= 0 {
+            return;
+        }
 
-            if offset_y < 0 {
+        let draw_start_y =
+            -sprite_height / 2 + camera.horizon + (v_offset * sprite_height as f32) as i32;
+        let draw_start_x = -sprite_width / 2 + sprite_screen_x;
+        let draw_end_x = draw_start_x + sprite_width;
+
+        let shade = (full_height / self.height as f32).clamp(0.0, 1.0);
+        let color = [
+            (255.0 * shade) as u8,
+            (255.0 * shade) as u8,
+            (255.0 * shade) as u8,
+            0xff,
+        ];
+        let fog = camera.fog_overlay(transform_y);
+
+        // draw the sprite one column at a time so that each column
+        // is only hidden by the walls directly in front of it
+        for stripe in draw_start_x.max(0)..draw_end_x.min(self.width) {
+            // columns are mirrored between sprite and screen space
+            let screen_x = self.width - stripe - 1;
+            if camera.is_occluded(screen_x, transform_y) {
                 continue;
             }
-            if offset_y >= self.height {
-                break;
-            }
 
-            for c_x in ((clamped_x as i32 - x) as u32)..size.width {
-                let offset_x = c_x as i32 + x;
+            // sampled through the middle of the screen column, flipped back to match
+            let across = ((stripe - draw_start_x) as f32 + 0.5) / sprite_width as f32;
+            let tex_x = texture.width() as f32 * (1.0 - across);
 
-                if offset_x < 0 {
-                    continue;
-                }
+            self.draw_texture_column(
+                texture,
+                &color,
+                &fog,
+                screen_x,
+                draw_start_y,
+                sprite_height as u32,
+                tex_x,
+                true,
+            );
+        }
+    }
 
-                if offset_x >= self.width as i32 {
-                    break;
-                }
+    /// draws a square dot centered at the given world position at eye level.
+    /// `size` is the width of the dot in world units, it is never smaller than a pixel
+    pub fn draw_particle(
+        &mut self,
+        color: &[u8; 4],
+        world_x: f32,
+        world_y: f32,
+        size: f32,
+        camera: &Camera,
+    ) {
+        let Some((center_x, depth)) = camera.project(world_x, world_y, self.width) else {
+            return;
+        };
 
-                let pix =
-                    texture.get_pixel((c_x as f32 * x_scale) as u32, (c_y as f32 * y_scale) as u32);
-                let color = [
-                    (pix[0] as f32 * (color[0] as f32 / 255.0)) as u8,
-                    (pix[1] as f32 * (color[1] as f32 / 255.0)) as u8,
-                    (pix[2] as f32 * (color[2] as f32 / 255.0)) as u8,
-                    (pix[3] as f32 * (color[3] as f32 / 255.0)) as u8,
-                ];
-                self.draw_pixel(&color, offset_x, offset_y);
+        let pixel_size = ((size * self.height as f32 / depth) as i32).max(1);
+        let color = blend_overlay(*color, &camera.fog_overlay(depth));
+        let start_x = center_x - pixel_size / 2;
+        let start_y = camera.horizon - pixel_size / 2;
+        for x in start_x.max(0)..(start_x + pixel_size).min(self.width) {
+            let screen_x = self.width - x - 1;
+            if camera.is_occluded(screen_x, depth) {
+                continue;
             }
+            self.draw_vert_line(&color, screen_x, start_y, pixel_size);
         }
     }
 
+    /// draws the given image at the specified x,y coords with the given size.
+    /// every pixel is multiplied by `tint`, white leaves the image unchanged
+    pub fn draw_texture(
+        &mut self,
+        texture: &DynamicImage,
+        tint: &[u8; 4],
+        x: i32,
+        y: i32,
+        size: PhysicalSize<u32>,
+    ) {
+        let whole_image = Rect {
+            x: 0,
+            y: 0,
+            width: texture.width(),
+            height: texture.height(),
+        };
+        self.draw_sub_texture(texture, tint, &[0, 0, 0, 0], x, y, size, whole_image);
+    }
+
+    /// draws part of the given image at the specified x,y coords with the given size.
+    /// every pixel is multiplied by `tint` and then has `overlay` blended
+    /// over it based on the overlay's alpha. only the part of `sub_image` inside
+    /// the image is drawn, nothing is drawn if that or the size is empty
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_sub_texture(
         &mut self,
         texture: &DynamicImage,
-        color: &[u8; 4],
+        tint: &[u8; 4],
+        overlay: &[u8; 4],
+        x: i32,
+        y: i32,
+        size: PhysicalSize<u32>,
+        sub_image: Rect,
+    ) {
+        self.draw_sub_texture_inner(texture, tint, overlay, x, y, size, sub_image, false);
+    }
+
+    /// same as `draw_sub_texture` but partially transparent pixels are
+    /// blended with what is already in the frame instead of replacing it
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sub_texture_blended(
+        &mut self,
+        texture: &DynamicImage,
+        tint: &[u8; 4],
+        overlay: &[u8; 4],
         x: i32,
         y: i32,
         size: PhysicalSize<u32>,
         sub_image: Rect,
     ) {
-        let subimage = texture.view(sub_image.x, sub_image.y, sub_image.width, sub_image.height);
+        self.draw_sub_texture_inner(texture, tint, overlay, x, y, size, sub_image, true);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sub_texture_inner(
+        &mut self,
+        texture: &DynamicImage,
+        tint: &[u8; 4],
+        overlay: &[u8; 4],
+        x: i32,
+        y: i32,
+        size: PhysicalSize<u32>,
+        sub_image: Rect,
+        blended: bool,
+    ) {
+        // only the part of the rect inside the texture is drawn
+        let left = sub_image.x.min(texture.width());
+        let top = sub_image.y.min(texture.height());
+        let right = sub_image
+            .x
+            .saturating_add(sub_image.width)
+            .min(texture.width());
+        let bottom = sub_image
+            .y
+            .saturating_add(sub_image.height)
+            .min(texture.height());
+        if right <= left || bottom <= top || size.width == 0 || size.height == 0 {
+            return;
+        }
+        // sampled from the whole texture so nothing outside the rect wraps in
+        let bounds = Rect {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        };
 
         let clamped_y = y.clamp(0, self.height) as u32;
         let clamped_x = x.clamp(0, self.width) as u32;
 
-        let x_scale = subimage.width() as f32 / size.width as f32;
-        let y_scale = subimage.height() as f32 / size.height as f32;
+        let x_scale = bounds.width as f32 / size.width as f32;
+        let y_scale = bounds.height as f32 / size.height as f32;
         for c_y in ((clamped_y as i32 - y) as u32)..size.height {
             let offset_y = c_y as i32 + y;
 
@@ -289,22 +943,378 @@ impl Renderer {
                     continue;
                 }
 
-                if offset_x >= self.width as i32 {
+                if offset_x >= self.width {
                     break;
                 }
 
-                let pix = subimage
-                    .get_pixel((c_x as f32 * x_scale) as u32, (c_y as f32 * y_scale) as u32);
-                if pix[3] != 0 {
-                    let color = [
-                        (pix[0] as f32 * (color[0] as f32 / 255.0)) as u8,
-                        (pix[1] as f32 * (color[1] as f32 / 255.0)) as u8,
-                        (pix[2] as f32 * (color[2] as f32 / 255.0)) as u8,
-                        (pix[3] as f32 * (color[3] as f32 / 255.0)) as u8,
-                    ];
-                    self.draw_pixel(&color, offset_x, offset_y);
-                }
+                // sampled through the middle of each pixel
+                let pix = sample_clamped(
+                    texture,
+                    left as f32 + (c_x as f32 + 0.5) * x_scale,
+                    top as f32 + (c_y as f32 + 0.5) * y_scale,
+                    self.filtering,
+                    bounds,
+                );
+                self.draw_texel(pix, tint, overlay, offset_x, offset_y, blended);
             }
         }
     }
+
+    /// draws a single column of the given image stretched over `height` rows downwards
+    /// from the given x,y coords. `tex_x` is the column of texels to draw and can be
+    /// fractional, so bilinear filtering blends it with the columns beside it. tinted and
+    /// overlaid like `draw_sub_texture`, and blended with the frame when `blended` is set
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_texture_column(
+        &mut self,
+        texture: &DynamicImage,
+        tint: &[u8; 4],
+        overlay: &[u8; 4],
+        x: i32,
+        y: i32,
+        height: u32,
+        tex_x: f32,
+        blended: bool,
+    ) {
+        if x < 0 || x >= self.width || height == 0 || texture.width() == 0 || texture.height() == 0
+        {
+            return;
+        }
+        let bounds = Rect {
+            x: 0,
+            y: 0,
+            width: texture.width(),
+            height: texture.height(),
+        };
+
+        let y_scale = texture.height() as f32 / height as f32;
+        let first = (-y).max(0) as u32;
+        let last = (self.height - y).clamp(0, height as i32) as u32;
+        for c_y in first..last {
+            let tex_y = (c_y as f32 + 0.5) * y_scale;
+            let pix = sample_clamped(texture, tex_x, tex_y, self.filtering, bounds);
+            self.draw_texel(pix, tint, overlay, x, c_y as i32 + y, blended);
+        }
+    }
+
+    /// tints and overlays a sampled texel and draws it, skipping fully transparent texels
+    fn draw_texel(
+        &mut self,
+        pix: [u8; 4],
+        tint: &[u8; 4],
+        overlay: &[u8; 4],
+        x: i32,
+        y: i32,
+        blended: bool,
+    ) {
+        if pix[3] == 0 {
+            return;
+        }
+        let color = [
+            (pix[0] as f32 * (tint[0] as f32 / 255.0)) as u8,
+            (pix[1] as f32 * (tint[1] as f32 / 255.0)) as u8,
+            (pix[2] as f32 * (tint[2] as f32 / 255.0)) as u8,
+            (pix[3] as f32 * (tint[3] as f32 / 255.0)) as u8,
+        ];
+        let color = blend_overlay(color, overlay);
+        if blended && color[3] != 0xff {
+            self.blend_pixel(&color, x, y);
+        } else {
+            self.draw_pixel(&color, x, y);
+        }
+    }
+}
+
+/// returns how much of a color at the given distance is hidden by fog, from 0.0 to 1.0
+pub fn fog_amount(dist: f32, density: f32) -> f32 {
+    1.0 - (-density * dist.max(0.0)).exp()
+}
+
+/// blends the overlay color over the given color using the overlay's alpha
+fn blend_overlay(color: [u8; 4], overlay: &[u8; 4]) -> [u8; 4] {
+    if overlay[3] == 0 {
+        return color;
+    }
+
+    let amount = overlay[3] as f32 / 255.0;
+    [
+        (color[0] as f32 * (1.0 - amount) + overlay[0] as f32 * amount) as u8,
+        (color[1] as f32 * (1.0 - amount) + overlay[1] as f32 * amount) as u8,
+        (color[2] as f32 * (1.0 - amount) + overlay[2] as f32 * amount) as u8,
+        color[3],
+    ]
+}
+
+/// samples the image at the given texel coordinates, wrapping around its edges.
+/// texel `n` covers the range `n..n + 1`
+pub fn sample<I>(image: &I, x: f32, y: f32, mode: SamplingMode) -> [u8; 4]
+where
+    I: GenericImageView<Pixel = Rgba<u8>>,
+{
+    let (width, height) = image.dimensions();
+    sample_texels(image, x, y, mode, |x, y| {
+        (
+            x.rem_euclid(width as i32) as u32,
+            y.rem_euclid(height as i32) as u32,
+        )
+    })
+}
+
+/// samples the image at the given texel coordinates like `sample`, but for images
+/// that don't tile. coordinates past the edges of `bounds` use the closest texel
+/// inside it, so nothing outside it is ever blended in. `bounds` must not be empty
+pub fn sample_clamped<I>(image: &I, x: f32, y: f32, mode: SamplingMode, bounds: Rect) -> [u8; 4]
+where
+    I: GenericImageView<Pixel = Rgba<u8>>,
+{
+    let (left, top) = (bounds.x as i32, bounds.y as i32);
+    let right = left + bounds.width as i32 - 1;
+    let bottom = top + bounds.height as i32 - 1;
+    sample_texels(image, x, y, mode, |x, y| {
+        (x.clamp(left, right) as u32, y.clamp(top, bottom) as u32)
+    })
+}
+
+/// samples the image, using `texel_at` to turn texel coordinates that may be
+/// outside the image into ones inside it
+fn sample_texels<I>(
+    image: &I,
+    x: f32,
+    y: f32,
+    mode: SamplingMode,
+    texel_at: impl Fn(i32, i32) -> (u32, u32),
+) -> [u8; 4]
+where
+    I: GenericImageView<Pixel = Rgba<u8>>,
+{
+    let texel = |x: i32, y: i32| {
+        let (x, y) = texel_at(x, y);
+        image.get_pixel(x, y).0
+    };
+
+    match mode {
+        SamplingMode::Nearest => texel(x.floor() as i32, y.floor() as i32),
+        SamplingMode::Bilinear => {
+            // offset so that the weights are relative to texel centers
+            let x = x - 0.5;
+            let y = y - 0.5;
+            let x0 = x.floor();
+            let y0 = y.floor();
+            let fx = x - x0;
+            let fy = y - y0;
+            let (x0, y0) = (x0 as i32, y0 as i32);
+
+            let top_left = texel(x0, y0);
+            let top_right = texel(x0 + 1, y0);
+            let bottom_left = texel(x0, y0 + 1);
+            let bottom_right = texel(x0 + 1, y0 + 1);
+
+            let mut color = [0; 4];
+            for (i, channel) in color.iter_mut().enumerate() {
+                let top = top_left[i] as f32 * (1.0 - fx) + top_right[i] as f32 * fx;
+                let bottom = bottom_left[i] as f32 * (1.0 - fx) + bottom_right[i] as f32 * fx;
+                *channel = (top * (1.0 - fy) + bottom * fy).round() as u8;
+            }
+            color
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+    /// indices of the pixels that aren't still cleared to zero
+    fn drawn_pixels(renderer: &Renderer) -> Vec<usize> {
+        let frame = renderer.frame_buffer.frame();
+        (0..frame.len() / 4)
+            .filter(|i| frame[i * 4..i * 4 + 4] != [0; 4])
+            .collect()
+    }
+
+    #[test]
+    fn draw_pixel_outside_frame_is_ignored() {
+        let mut renderer = Renderer::headless(8, 4);
+        for (x, y) in [(8, 0), (-1, 0), (0, 4), (0, -1)] {
+            renderer.draw_pixel(&WHITE, x, y);
+        }
+        assert!(drawn_pixels(&renderer).is_empty());
+
+        renderer.draw_pixel(&WHITE, 7, 3);
+        assert_eq!(drawn_pixels(&renderer), [31]);
+    }
+
+    #[test]
+    fn lines_reach_the_bottom_edge() {
+        let mut renderer = Renderer::headless(8, 4);
+        assert_eq!(renderer.draw_hori_line(&WHITE, -2, 3, 20), 8);
+        assert_eq!(drawn_pixels(&renderer), (24..32).collect::<Vec<_>>());
+
+        let mut renderer = Renderer::headless(8, 4);
+        // runs off the bottom so only the last row is drawn
+        assert_eq!(renderer.draw_vert_line(&WHITE, 5, 3, 4), 1);
+        assert_eq!(drawn_pixels(&renderer), [29]);
+        assert_eq!(renderer.draw_vert_line(&WHITE, 5, 4, 4), -1);
+    }
+
+    #[test]
+    fn sampling_non_power_of_two_texture_stays_inside() {
+        // each texel holds its own coordinates
+        let texture = RgbaImage::from_fn(48, 48, |x, y| Rgba([x as u8, y as u8, 0, 0xff]));
+        assert_eq!(
+            sample(&texture, 47.99, 0.5, SamplingMode::Nearest),
+            [47, 0, 0, 0xff]
+        );
+        assert_eq!(
+            sample(&texture, 48.2, 1.5, SamplingMode::Nearest),
+            [0, 1, 0, 0xff]
+        );
+        assert_eq!(
+            sample(&texture, -0.25, 47.5, SamplingMode::Nearest),
+            [47, 47, 0, 0xff]
+        );
+
+        for step in 0..=200 {
+            let coord = step as f32 * 0.24;
+            for mode in [SamplingMode::Nearest, SamplingMode::Bilinear] {
+                let [x, y, _, alpha] = sample(&texture, coord, 48.0 - coord, mode);
+                assert!(x < 48 && y < 48 && alpha == 0xff);
+            }
+        }
+    }
+
+    #[test]
+    fn sub_texture_rects_are_clamped_to_texture() {
+        let texture = DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 4, |x, _| {
+            Rgba([x as u8 + 1, 0, 0, 0xff])
+        }));
+        let draw = |x, y, width, height, size| {
+            let mut renderer = Renderer::headless(4, 4);
+            let rect = Rect {
+                x,
+                y,
+                width,
+                height,
+            };
+            renderer.draw_sub_texture(&texture, &WHITE, &[0; 4], 0, 0, size, rect);
+            renderer
+        };
+        let full = PhysicalSize::new(4, 4);
+
+        // exactly the whole texture
+        assert_eq!(drawn_pixels(&draw(0, 0, 4, 4, full)).len(), 16);
+        // only the two columns inside the texture are stretched over the output
+        let renderer = draw(2, 0, 4, 4, full);
+        let row: Vec<u8> = renderer.frame_buffer.frame()[..16]
+            .chunks_exact(4)
+            .map(|pixel| pixel[0])
+            .collect();
+        assert_eq!(row, [3, 3, 4, 4]);
+        // starting on or past the far edge leaves nothing to draw
+        assert!(drawn_pixels(&draw(4, 0, 4, 4, full)).is_empty());
+        assert!(drawn_pixels(&draw(0, 4, 4, 4, full)).is_empty());
+        assert!(drawn_pixels(&draw(u32::MAX, 0, u32::MAX, 4, full)).is_empty());
+        // empty rects and sizes
+        assert!(drawn_pixels(&draw(0, 0, 0, 4, full)).is_empty());
+        assert!(drawn_pixels(&draw(0, 0, 4, 0, full)).is_empty());
+        assert!(drawn_pixels(&draw(0, 0, 4, 4, PhysicalSize::new(0, 4))).is_empty());
+    }
+
+    #[test]
+    fn sprite_strips_cover_sprite_width() {
+        let texture = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba(WHITE)));
+        // looking along positive x with a 90 degree field of view
+        let camera = Camera {
+            dir_x: 1.0,
+            plane_y: 0.5,
+            horizon: 16,
+            ..Default::default()
+        };
+        // a sprite two tiles away is as wide as half the screen height
+        for (world_y, expected_columns) in [(-0.5, 16), (0.0, 16), (0.5, 16), (0.9, 12)] {
+            let mut renderer = Renderer::headless(64, 32);
+            renderer.draw_sprite(&texture, 2.0, world_y, 1.0, 0.0, &camera);
+            let mut columns: Vec<usize> = drawn_pixels(&renderer)
+                .into_iter()
+                .map(|i| i % 64)
+                .collect();
+            columns.sort_unstable();
+            columns.dedup();
+            assert_eq!(columns.len(), expected_columns, "sprite at y {world_y}");
+            // the strips are side by side without gaps
+            assert_eq!(
+                columns[columns.len() - 1] - columns[0] + 1,
+                expected_columns
+            );
+        }
+    }
+
+    #[test]
+    fn filled_circle_covers_its_area() {
+        let mut renderer = Renderer::headless(64, 64);
+        renderer.draw_circle(&WHITE, 32, 32, 10, true);
+        let drawn = drawn_pixels(&renderer);
+        // pixels are included when their center is inside, so the edge reaches half a pixel further
+        let area = std::f32::consts::PI * 10.5 * 10.5;
+        assert!(
+            (drawn.len() as f32 - area).abs() < area * 0.05,
+            "{} pixels",
+            drawn.len()
+        );
+        for i in drawn {
+            let (x, y) = ((i % 64) as f32 - 32.0, (i / 64) as f32 - 32.0);
+            assert!((x * x + y * y).sqrt() <= 10.5);
+        }
+
+        // only the part on screen is drawn when the circle hangs off the edges
+        let mut renderer = Renderer::headless(8, 8);
+        renderer.draw_circle(&WHITE, 0, 0, 20, true);
+        assert_eq!(drawn_pixels(&renderer).len(), 64);
+        renderer.draw_circle(&WHITE, 100, 100, 3, true);
+    }
+
+    #[test]
+    fn filled_triangle_covers_its_area() {
+        let mut renderer = Renderer::headless(64, 64);
+        renderer.draw_triangle(&WHITE, (10, 10), (50, 10), (10, 50), true);
+        let drawn = drawn_pixels(&renderer);
+        // a right triangle with 40 pixel legs, plus the pixels along its edges
+        let area = 40.0 * 40.0 / 2.0;
+        assert!(
+            (drawn.len() as f32 - area).abs() < area * 0.1,
+            "{} pixels",
+            drawn.len()
+        );
+        for i in drawn {
+            let (x, y) = ((i % 64) as i32, (i / 64) as i32);
+            assert!(x >= 10 && y >= 10 && x + y <= 61);
+        }
+
+        // points far outside the frame only fill the part on screen
+        let mut renderer = Renderer::headless(8, 8);
+        renderer.draw_triangle(&WHITE, (i32::MIN, -4), (i32::MAX, -4), (0, i32::MAX), true);
+        assert_eq!(drawn_pixels(&renderer).len(), 64);
+    }
+
+    #[test]
+    fn bilinear_columns_blend_sideways_but_not_around() {
+        // black on the left, white on the right, with a red top row and blue bottom row
+        let texture = DynamicImage::ImageRgba8(RgbaImage::from_fn(2, 4, |x, y| match y {
+            0 => Rgba([0xff, 0, 0, 0xff]),
+            3 => Rgba([0, 0, 0xff, 0xff]),
+            _ => Rgba([x as u8 * 0xff, x as u8 * 0xff, x as u8 * 0xff, 0xff]),
+        }));
+        let mut renderer = Renderer::headless(1, 4);
+        renderer.filtering = SamplingMode::Bilinear;
+        renderer.draw_texture_column(&texture, &WHITE, &[0; 4], 0, 0, 4, 1.0, false);
+        let column = renderer.capture();
+
+        // halfway between the two columns
+        assert_eq!(column.get_pixel(0, 1).0, [0x80, 0x80, 0x80, 0xff]);
+        // the edges are held rather than blending with the opposite side
+        assert_eq!(column.get_pixel(0, 0).0, [0xff, 0, 0, 0xff]);
+        assert_eq!(column.get_pixel(0, 3).0, [0, 0, 0xff, 0xff]);
+    }
 }
@@ -0,0 +1,30 @@
+// draws the frame texture over a full screen triangle, the transform
+// shrinks it into the area it's scaled to on the window
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+struct Locals {
+    transform: mat4x4<f32>,
+}
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, tex_coord);
+}
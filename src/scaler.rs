@@ -0,0 +1,250 @@
+use pixels::wgpu::{self, util::DeviceExt};
+use pixels::Pixels;
+
+/// how the frame is fit into a window with a different size or shape
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// scales the frame as large as it fits without distorting it,
+    /// filling the rest of the window with black bars
+    #[default]
+    PreserveAspect,
+    /// stretches the frame over the whole window
+    Stretch,
+    /// only scales the frame by whole numbers so every pixel is the same size,
+    /// falling back to `PreserveAspect` when the window is smaller than the frame
+    IntegerOnly,
+}
+
+/// draws the frame texture onto the window, scaled according to a `ScalingMode`.
+/// replaces the renderer built into `pixels` which only supports whole number scales
+pub struct Scaler {
+    vertex_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    texture_size: (f32, f32),
+    /// area of the window the frame covers, as x, y, width and height
+    clip_rect: (u32, u32, u32, u32),
+}
+
+impl Scaler {
+    /// builds the pipeline for the current frame texture, this has to be
+    /// done again whenever the frame buffer is resized
+    pub fn new(pixels: &Pixels, surface_size: (u32, u32), mode: ScalingMode) -> Self {
+        let device = pixels.device();
+        let texture = pixels.texture();
+        let texture_size = (texture.width() as f32, texture.height() as f32);
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("scale.wgsl"));
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("scaler_sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // a single triangle that covers the whole screen
+        let vertices: [f32; 6] = [-1.0, -1.0, 3.0, -1.0, -1.0, 3.0];
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("scaler_vertex_buffer"),
+            contents: &to_bytes(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let (transform, clip_rect) = scaling_transform(texture_size, surface_size, mode);
+        let transform = to_bytes(&transform);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("scaler_uniform_buffer"),
+            contents: &transform,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("scaler_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(transform.len() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("scaler_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("scaler_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("scaler_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: 2 * std::mem::size_of::<f32>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Float32x2,
+                        offset: 0,
+                        shader_location: 0,
+                    }],
+                }],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: pixels.render_texture_format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            vertex_buffer,
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            texture_size,
+            clip_rect,
+        }
+    }
+
+    /// refits the frame to a new window size or scaling mode
+    pub fn resize(&mut self, pixels: &Pixels, surface_size: (u32, u32), mode: ScalingMode) {
+        let (transform, clip_rect) = scaling_transform(self.texture_size, surface_size, mode);
+        pixels
+            .queue()
+            .write_buffer(&self.uniform_buffer, 0, &to_bytes(&transform));
+        self.clip_rect = clip_rect;
+    }
+
+    /// draws the frame, anything outside of it is cleared to black
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_target: &wgpu::TextureView) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("scaler_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        let (x, y, width, height) = self.clip_rect;
+        // a minimized window has nothing to draw into
+        if width == 0 || height == 0 {
+            return;
+        }
+        pass.set_pipeline(&self.render_pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        pass.set_scissor_rect(x, y, width, height);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// works out the transform that fits the texture onto the screen
+/// and the area of the screen that it ends up covering
+fn scaling_transform(
+    texture_size: (f32, f32),
+    surface_size: (u32, u32),
+    mode: ScalingMode,
+) -> ([f32; 16], (u32, u32, u32, u32)) {
+    let (texture_width, texture_height) = texture_size;
+    let (screen_width, screen_height) = (surface_size.0 as f32, surface_size.1 as f32);
+    if screen_width <= 0.0 || screen_height <= 0.0 {
+        return ([0.0; 16], (0, 0, 0, 0));
+    }
+
+    let fit_scale = (screen_width / texture_width).min(screen_height / texture_height);
+    let (scaled_width, scaled_height) = match mode {
+        ScalingMode::PreserveAspect => (texture_width * fit_scale, texture_height * fit_scale),
+        ScalingMode::Stretch => (screen_width, screen_height),
+        ScalingMode::IntegerOnly => {
+            let scale = if fit_scale >= 1.0 {
+                fit_scale.floor()
+            } else {
+                fit_scale
+            };
+            (texture_width * scale, texture_height * scale)
+        }
+    };
+
+    let scale_x = scaled_width / screen_width;
+    let scale_y = scaled_height / screen_height;
+    // nudges the frame by half a pixel on odd sized windows so texels line up with pixels
+    let offset_x = (screen_width / 2.0).fract() / screen_width;
+    let offset_y = (screen_height / 2.0).fract() / screen_height;
+    #[rustfmt::skip]
+    let transform = [
+        scale_x,  0.0,      0.0, 0.0,
+        0.0,      scale_y,  0.0, 0.0,
+        0.0,      0.0,      1.0, 0.0,
+        offset_x, offset_y, 0.0, 1.0,
+    ];
+
+    let clip_rect = (
+        ((screen_width - scaled_width) / 2.0) as u32,
+        ((screen_height - scaled_height) / 2.0) as u32,
+        scaled_width as u32,
+        scaled_height as u32,
+    );
+    (transform, clip_rect)
+}
+
+fn to_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect()
+}
@@ -0,0 +1,293 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use image::math::Rect;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use winit::dpi::PhysicalSize;
+
+use crate::map::{self, Map};
+use crate::renderer::{sample, Camera, Renderer, SamplingMode};
+use crate::textures::TextureManager;
+use crate::{apply_fog, light_at, lit_brightness, Light, Ray};
+
+/// everything needed to draw a frame, worked out by `App::render_scene` without
+/// touching the renderer. textures are referred to by id so a frame doesn't
+/// borrow from the game and can be built on another thread. a frame can be
+/// refilled with `App::render_scene_into` to reuse its buffers. maps are shared
+/// with the game rather than copied, so they're only copied when a tile changes
+#[derive(Default)]
+pub struct FrameCommands {
+    /// camera sprites and particles are projected with
+    pub camera: Camera,
+    /// wall hit by the ray through each screen column
+    pub z_buffer: Vec<Ray>,
+    /// floor and ceiling drawn by `DrawCommand::Floor`
+    pub floor: FloorCast,
+    /// walls drawn by `DrawCommand::Minimap`
+    pub minimap_walls: Arc<Map>,
+    /// drawn in order, later commands draw over earlier ones
+    pub commands: Vec<DrawCommand>,
+    /// entities in the order they're drawn along with their squared distance,
+    /// only kept between frames so the allocation is reused
+    pub(crate) sprite_order: Vec<(usize, f32)>,
+}
+
+/// a single call into the renderer
+pub enum DrawCommand {
+    Fill([u8; 4]),
+    FillBlended([u8; 4]),
+    Vignette([u8; 4]),
+    VerticalGradient {
+        top_color: [u8; 4],
+        bottom_color: [u8; 4],
+        y0: i32,
+        y1: i32,
+    },
+    /// a column of texels stretched down the screen, used for wall and sky columns
+    TextureColumn {
+        texture: usize,
+        tint: [u8; 4],
+        overlay: [u8; 4],
+        x: i32,
+        y: i32,
+        height: u32,
+        /// texel column to draw, fractional so filtering can blend the columns beside it
+        tex_x: f32,
+    },
+    /// part of a texture stretched over a rectangle of the screen, used for the viewmodel
+    SubTexture {
+        texture: usize,
+        tint: [u8; 4],
+        overlay: [u8; 4],
+        x: i32,
+        y: i32,
+        size: PhysicalSize<u32>,
+        sub_image: Rect,
+        /// blends partially transparent pixels with the frame instead of replacing it
+        blended: bool,
+    },
+    /// the textured floor and ceiling, cast from the frame's `floor`
+    Floor,
+    Sprite {
+        texture: usize,
+        world_x: f32,
+        world_y: f32,
+        scale: f32,
+        v_offset: f32,
+    },
+    Particle {
+        color: [u8; 4],
+        world_x: f32,
+        world_y: f32,
+        size: f32,
+    },
+    Crosshair {
+        color: [u8; 4],
+        center_x: i32,
+        center_y: i32,
+        size: i32,
+        gap: i32,
+    },
+    /// the frame's `minimap_walls` around the camera
+    Minimap {
+        origin_x: i32,
+        origin_y: i32,
+        cell_size: i32,
+        radius: i32,
+    },
+}
+
+/// view of the floor and ceiling for one frame, cast row by row when applied
+/// since it's done per pixel rather than per column
+#[derive(Default)]
+pub struct FloorCast {
+    pub floor: Arc<Map>,
+    pub ceiling: Arc<Map>,
+    pub horizon: i32,
+    pub player_x: f32,
+    pub player_y: f32,
+    /// directions of the rays through the leftmost and rightmost columns
+    pub ray_dir_left: (f32, f32),
+    pub ray_dir_right: (f32, f32),
+    /// rows covered by the wall in each column, the floor and ceiling
+    /// aren't cast there since the wall is drawn over them anyway
+    pub wall_rows: Vec<Range<i32>>,
+    /// leaves the rows above the horizon alone for the sky
+    pub skip_ceiling: bool,
+    pub lights: Vec<Light>,
+    pub filtering: SamplingMode,
+    pub fog_color: [u8; 4],
+    pub fog_density: f32,
+}
+
+impl FrameCommands {
+    /// replays the commands onto the renderer
+    pub fn apply(&self, renderer: &mut Renderer, textures: &TextureManager) {
+        for command in &self.commands {
+            match command {
+                DrawCommand::Fill(color) => renderer.fill(color),
+                DrawCommand::FillBlended(color) => renderer.fill_blended(color),
+                DrawCommand::Vignette(color) => renderer.draw_vignette(color),
+                DrawCommand::VerticalGradient {
+                    top_color,
+                    bottom_color,
+                    y0,
+                    y1,
+                } => renderer.draw_vertical_gradient(top_color, bottom_color, *y0, *y1),
+                DrawCommand::TextureColumn {
+                    texture,
+                    tint,
+                    overlay,
+                    x,
+                    y,
+                    height,
+                    tex_x,
+                } => renderer.draw_texture_column(
+                    textures.texture_or_fallback(*texture),
+                    tint,
+                    overlay,
+                    *x,
+                    *y,
+                    *height,
+                    *tex_x,
+                    false,
+                ),
+                DrawCommand::SubTexture {
+                    texture,
+                    tint,
+                    overlay,
+                    x,
+                    y,
+                    size,
+                    sub_image,
+                    blended,
+                } => {
+                    let texture = textures.texture_or_fallback(*texture);
+                    if *blended {
+                        renderer.draw_sub_texture_blended(
+                            texture, tint, overlay, *x, *y, *size, *sub_image,
+                        );
+                    } else {
+                        renderer
+                            .draw_sub_texture(texture, tint, overlay, *x, *y, *size, *sub_image);
+                    }
+                }
+                DrawCommand::Floor => self.floor.cast(renderer, textures),
+                DrawCommand::Sprite {
+                    texture,
+                    world_x,
+                    world_y,
+                    scale,
+                    v_offset,
+                } => renderer.draw_sprite(
+                    textures.texture_or_fallback(*texture),
+                    *world_x,
+                    *world_y,
+                    *scale,
+                    *v_offset,
+                    &self.camera,
+                ),
+                DrawCommand::Particle {
+                    color,
+                    world_x,
+                    world_y,
+                    size,
+                } => renderer.draw_particle(color, *world_x, *world_y, *size, &self.camera),
+                DrawCommand::Crosshair {
+                    color,
+                    center_x,
+                    center_y,
+                    size,
+                    gap,
+                } => renderer.draw_crosshair(color, *center_x, *center_y, *size, *gap),
+                DrawCommand::Minimap {
+                    origin_x,
+                    origin_y,
+                    cell_size,
+                    radius,
+                } => renderer.draw_minimap(
+                    &self.minimap_walls,
+                    self.camera.x,
+                    self.camera.y,
+                    self.camera.dir_x,
+                    self.camera.dir_y,
+                    *origin_x,
+                    *origin_y,
+                    *cell_size,
+                    *radius,
+                ),
+            }
+        }
+    }
+}
+
+impl FloorCast {
+    /// each row below the horizon is floor and each row above it is ceiling,
+    /// the rows are cast in parallel straight into the frame
+    fn cast(&self, renderer: &mut Renderer, textures: &TextureManager) {
+        let width = renderer.width();
+        let height = renderer.height();
+        let inv_width = 1.0 / width as f32;
+        let inv_height = 1.0 / height as f32;
+        let (ray_dir_x0, ray_dir_y0) = self.ray_dir_left;
+        let (ray_dir_x1, ray_dir_y1) = self.ray_dir_right;
+
+        renderer.rows_mut().enumerate().for_each(|(y, row)| {
+            let y = y as i32;
+            let is_floor = y > self.horizon;
+            if y == self.horizon || (!is_floor && self.skip_ceiling) {
+                return;
+            }
+            let tiles = if is_floor { &self.floor } else { &self.ceiling };
+
+            let rows_from_horizon = (y - self.horizon).abs();
+
+            // minimal division distance calculation
+            let row_dist = height as f32 / (rows_from_horizon << 1) as f32;
+
+            let floor_step_x = row_dist * (ray_dir_x1 - ray_dir_x0) * inv_width;
+            let floor_step_y = row_dist * (ray_dir_y1 - ray_dir_y0) * inv_width;
+
+            let mut floor_x = self.player_x + row_dist * ray_dir_x0;
+            let mut floor_y = self.player_y + row_dist * ray_dir_y0;
+
+            let line_height = rows_from_horizon << 1;
+            let shade = line_height as f32 * inv_height;
+
+            for x in 0..width as usize {
+                if self.wall_rows[x].contains(&y) {
+                    floor_x += floor_step_x;
+                    floor_y += floor_step_y;
+                    continue;
+                }
+
+                let cell_x = floor_x as i32;
+                let cell_y = floor_y as i32;
+
+                let (id, flags) = map::split_tile(tiles.tile_at(cell_x, cell_y));
+                if id > 0 {
+                    let texture = textures.texture_or_fallback(id as usize - 1);
+                    let (u, v) =
+                        map::orient(flags, floor_x - cell_x as f32, floor_y - cell_y as f32);
+                    let tex_x = texture.width() as f32 * u;
+                    let tex_y = texture.height() as f32 * v;
+
+                    let light = light_at(&self.lights, floor_x, floor_y);
+                    let brightness = lit_brightness(shade, light);
+                    let color = sample(texture, tex_x, tex_y, self.filtering);
+                    let color = [
+                        (color[0] as f32 * brightness[0]) as u8,
+                        (color[1] as f32 * brightness[1]) as u8,
+                        (color[2] as f32 * brightness[2]) as u8,
+                        (color[3] as f32 * shade).clamp(0.0, 255.0) as u8,
+                    ];
+                    let color = apply_fog(color, row_dist, self.fog_density, self.fog_color);
+                    row[x * 4..x * 4 + 4].copy_from_slice(&color);
+                }
+
+                floor_x += floor_step_x;
+                floor_y += floor_step_y;
+            }
+        });
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Index;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, Rgba, RgbaImage};
+
+/// owns every loaded texture and hands out stable ids for them
+#[derive(Default)]
+pub struct TextureManager {
+    textures: Vec<DynamicImage>,
+    names: HashMap<String, usize>,
+    /// drawn in place of any id that doesn't have a texture
+    missing_texture_id: Option<usize>,
+}
+
+impl TextureManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a texture without a name, returning its id.
+    /// textures are stored as rgba8 so every texture samples the same way
+    pub fn push(&mut self, texture: DynamicImage) -> usize {
+        let texture = match texture {
+            DynamicImage::ImageRgba8(_) => texture,
+            _ => DynamicImage::ImageRgba8(texture.into_rgba8()),
+        };
+        self.textures.push(texture);
+        self.textures.len() - 1
+    }
+
+    /// adds a texture that can later be looked up with `texture_id`
+    pub fn push_named(&mut self, name: impl Into<String>, texture: DynamicImage) -> usize {
+        let id = self.push(texture);
+        self.names.insert(name.into(), id);
+        id
+    }
+
+    /// loads a single image, naming it after its file name without the extension
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref();
+        let texture = image::open(path)
+            .with_context(|| format!("failed to load texture {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(self.push_named(name, texture))
+    }
+
+    /// loads every image listed in a manifest, in order.
+    /// the manifest is a ron list of file names relative to its own directory
+    pub fn load_manifest(&mut self, path: impl AsRef<Path>) -> Result<Vec<usize>> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read texture manifest {}", path.display()))?;
+        let files: Vec<String> = ron::from_str(&contents)
+            .with_context(|| format!("failed to parse texture manifest {}", path.display()))?;
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        files.iter().map(|file| self.load(dir.join(file))).collect()
+    }
+
+    /// adds the texture drawn for out of range ids, returning its id
+    pub fn push_missing_texture(&mut self, texture: DynamicImage) -> usize {
+        let id = self.push(texture);
+        self.missing_texture_id = Some(id);
+        id
+    }
+
+    pub fn missing_texture_id(&self) -> Option<usize> {
+        self.missing_texture_id
+    }
+
+    /// returns the texture with the given id, or the missing texture if there isn't one.
+    /// the generated checkerboard is used when no missing texture has been pushed
+    pub fn texture_or_fallback(&self, id: usize) -> &DynamicImage {
+        static FALLBACK: OnceLock<DynamicImage> = OnceLock::new();
+        match (self.textures.get(id), self.missing_texture_id) {
+            (Some(texture), _) => texture,
+            (None, Some(missing)) => &self.textures[missing],
+            (None, None) => FALLBACK.get_or_init(missing_texture),
+        }
+    }
+
+    /// looks up the id of a texture by name
+    pub fn texture_id(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&DynamicImage> {
+        self.textures.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+}
+
+impl Index<usize> for TextureManager {
+    type Output = DynamicImage;
+
+    fn index(&self, id: usize) -> &Self::Output {
+        &self.textures[id]
+    }
+}
+
+/// magenta and black checkerboard that stands out wherever a texture is missing
+pub fn missing_texture() -> DynamicImage {
+    const SIZE: u32 = 64;
+    const CHECK_SIZE: u32 = 8;
+    let image = RgbaImage::from_fn(SIZE, SIZE, |x, y| {
+        if (x / CHECK_SIZE + y / CHECK_SIZE).is_multiple_of(2) {
+            Rgba([0xff, 0, 0xff, 0xff])
+        } else {
+            Rgba([0, 0, 0, 0xff])
+        }
+    });
+    DynamicImage::ImageRgba8(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::Renderer;
+    use image::{ImageFormat, RgbImage};
+    use std::io::Cursor;
+    use winit::dpi::PhysicalSize;
+
+    #[test]
+    fn bad_id_without_missing_texture_falls_back() {
+        let mut textures = TextureManager::new();
+        textures.push(DynamicImage::ImageRgba8(RgbaImage::new(4, 4)));
+        let fallback = textures.texture_or_fallback(7);
+        assert_eq!(fallback, &missing_texture());
+    }
+
+    #[test]
+    fn rgb_png_draws_like_rgba() {
+        let mut png = Vec::new();
+        RgbImage::from_pixel(3, 3, image::Rgb([10, 20, 30]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let loaded = image::load_from_memory(&png).unwrap();
+        assert!(matches!(loaded, DynamicImage::ImageRgb8(_)));
+
+        let mut textures = TextureManager::new();
+        let id = textures.push(loaded);
+        let mut renderer = Renderer::headless(4, 4);
+        renderer.draw_texture(&textures[id], &[0xff; 4], 0, 0, PhysicalSize::new(4, 4));
+        // missing alpha is treated as fully opaque
+        assert_eq!(renderer.capture().get_pixel(3, 3).0, [10, 20, 30, 0xff]);
+    }
+}